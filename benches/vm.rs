@@ -0,0 +1,91 @@
+//! Compares the tree-walking `AsonExpr::eval` with the bytecode VM.
+//! Run with `cargo bench`.
+//!
+//! `vm` runs a program compiled once; `compile+vm` recompiles it before every
+//! run, which is what evaluating a document does.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use ason::ast::AsonExpr;
+use ason::lexer::Lexer;
+use ason::limits::Limits;
+use ason::parser::Parser;
+use ason::vm::{Program, Vm};
+use ason::{AsonValue, Environment};
+
+const ITERATIONS: u32 = 2_000;
+
+fn environment() -> Environment {
+    let mut env = Environment::with_limits(Limits { max_steps: u64::MAX, ..Limits::default() });
+    env.add_constant("rate".into(), AsonValue::from(3));
+    env.add_constant("base".into(), AsonValue::from(100));
+    env
+}
+
+fn expression(source: &str, env: &mut Environment) -> AsonExpr {
+    let mut lexer = Lexer::new(source);
+    lexer.scan().unwrap();
+    let tokens = lexer.into_tokens();
+    Parser::new(&tokens, "bench".into(), env).expression().unwrap()
+}
+
+/// The average time of one call to `f`.
+fn time(mut f: impl FnMut()) -> Duration {
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn bench(name: &str, source: &str) {
+    let mut env = environment();
+    let expr = expression(source, &mut env);
+    let program = Program::compile(&expr);
+    let mut vm = Vm::new();
+    assert_eq!(vm.run(&program, &mut env).unwrap(), expr.eval(&mut env).unwrap());
+
+    let tree = time(|| _ = black_box(expr.eval(&mut env).unwrap()));
+    let compiled = time(|| _ = black_box(vm.run(&program, &mut env).unwrap()));
+    let mut scratch = Program::default();
+    let one_shot = time(|| {
+        scratch.recompile(&expr);
+        _ = black_box(vm.run(&scratch, &mut env).unwrap());
+    });
+    println!(
+        "{:<12} {:>6} ops  tree-walk {:>10.2?}  vm {:>10.2?} ({:.2}x)  compile+vm {:>10.2?} ({:.2}x)",
+        name,
+        program.code().len(),
+        tree,
+        compiled,
+        tree.as_secs_f64() / compiled.as_secs_f64(),
+        one_shot,
+        tree.as_secs_f64() / one_shot.as_secs_f64(),
+    );
+}
+
+/// `count` expression-s like `)rate 2 *(` summed together.
+fn sum_of(count: usize, term: &str) -> String {
+    format!("){} +(", vec![term; count].join(" "))
+}
+
+/// `)))1 2 +( 2 +( 2 +(`, `depth` levels deep.
+fn nested(depth: usize) -> String {
+    let mut source = "1".to_string();
+    for _ in 0..depth {
+        source = format!("){} 2 +(", source);
+    }
+    source
+}
+
+fn main() {
+    bench("literals", &sum_of(500, "1"));
+    bench("calls", &sum_of(500, ")1 2 *("));
+    bench("symbols", &sum_of(500, ")rate base *("));
+    bench("nested", &nested(200));
+    bench("strings", &format!("){} concat(", vec!["\"ab\" rate"; 200].join(" ")));
+}
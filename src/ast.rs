@@ -1,29 +1,61 @@
 use core::fmt;
 use std::collections::HashMap;
 use std::ops;
+use std::path::Path;
 
-use crate::token::TokenList;
 use crate::runtime::{AsonFunction, RuntimeError};
-use crate::parser::{Parser, ParserResult};
-use crate::lexer::Lexer;
+use crate::parser::{tokenize, Documents, Parser, ParserResult};
 use crate::environment::Environment;
+use crate::json::{JsonReader, JsonResult};
+use crate::span::Span;
 
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum AsonExpr {
-    Value(AsonValue),
-    Symbol(String),
-    ExprS(Vec<AsonExpr>, String),
-    None,
+    Value(AsonValue, Span),
+    Symbol(String, Span),
+    ExprS(Vec<AsonExpr>, String, Span),
+    None(Span),
 }
 
 impl AsonExpr {
+    pub fn span(&self) -> Span {
+        match self {
+            AsonExpr::Value(_, span) | AsonExpr::Symbol(_, span) | AsonExpr::ExprS(_, _, span) | AsonExpr::None(span) => *span,
+        }
+    }
+
+    /// Writes the expression back as ASON source.
+    pub fn to_ason(&self) -> String {
+        match self {
+            AsonExpr::Value(value, _) => value.to_ason(),
+            AsonExpr::Symbol(name, _) => name.clone(),
+            AsonExpr::ExprS(args, callee, _) => {
+                let mut ason = ")".to_string();
+                for arg in args {
+                    ason.push_str(&arg.to_ason());
+                    ason.push(' ');
+                }
+                ason.push_str(callee);
+                ason.push('(');
+                ason
+            }
+            AsonExpr::None(_) => ")(".into(),
+        }
+    }
+
     pub fn eval(&self, env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+        env.step()?;
         match self {
-            AsonExpr::Value(ason_value) => Ok((*ason_value).clone()),
-            AsonExpr::ExprS(vec, callee) => self.eval_expr_s(vec, &callee, env),
-            AsonExpr::None => Ok(AsonValue::Null),
-            AsonExpr::Symbol(id) => {
+            AsonExpr::Value(ason_value, _) => Ok((*ason_value).clone()),
+            AsonExpr::ExprS(vec, callee, _) => {
+                env.enter()?;
+                let result = self.eval_expr_s(vec, callee, env);
+                env.leave();
+                result
+            },
+            AsonExpr::None(_) => Ok(AsonValue::Null),
+            AsonExpr::Symbol(id, _) => {
                 if !env.symbols.contains_key(id) {
                     return Err(RuntimeError::UndefinedSymbol);
                 }
@@ -32,13 +64,13 @@ impl AsonExpr {
         }
     }
 
-    fn eval_expr_s(&self, params: &Vec<AsonExpr>, callee: &String, env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    fn eval_expr_s(&self, params: &[AsonExpr], callee: &str, env: &mut Environment) -> Result<AsonValue, RuntimeError> {
         let mut args = vec![];
         for param in params {
             args.push(param.eval(env)?);
         }
 
-        env.call_fn(&callee, args)
+        env.call_fn(callee, args)
     }
 }
 
@@ -117,11 +149,11 @@ impl ops::Div for AsonNumber {
     }
 }
 
-impl AsonNumber {
-    pub fn to_string(&self) -> String {
+impl fmt::Display for AsonNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            AsonNumber::Integer(i) => i.to_string(),
-            AsonNumber::Float(f) => f.to_string(),
+            AsonNumber::Integer(i) => write!(f, "{}", i),
+            AsonNumber::Float(v) => write!(f, "{}", v),
         }
     }
 }
@@ -138,7 +170,26 @@ pub enum AsonValue {
     Null,
 }
 
+#[allow(dead_code)]
 impl AsonValue {
+    /// The name of the value's type, as JSON Schema spells it.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            AsonValue::Function(_) => "function",
+            AsonValue::Object(_) => "object",
+            AsonValue::Array(_) => "array",
+            AsonValue::String(_) => "string",
+            AsonValue::Number(AsonNumber::Integer(_)) => "integer",
+            AsonValue::Number(AsonNumber::Float(_)) => "number",
+            AsonValue::Boolean(_) => "boolean",
+            AsonValue::Null => "null",
+        }
+    }
+
+    pub fn is_function(&self) -> bool {
+        matches!(*self, AsonValue::Function(_))
+    }
+
     pub fn is_object(&self) -> bool {
         matches!(*self, AsonValue::Object(_))
     }
@@ -192,74 +243,275 @@ impl AsonValue {
         }
     }
 
-    pub fn from_ason_string(s: &str) -> ParserResult<AsonValue> {
-        let tokens: *mut TokenList = &mut TokenList::new();
-        {
-            let mut lex = Lexer::new(s, unsafe{&mut *tokens});
-            _ = lex.scan();
-            // println!("{:#?}", unsafe{&*tokens});
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AsonValue::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            AsonValue::Number(AsonNumber::Integer(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Integers are converted, so any number can be read as a float.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            AsonValue::Number(AsonNumber::Integer(v)) => Some(*v as f64),
+            AsonValue::Number(AsonNumber::Float(v)) => Some(*v),
+            _ => None,
         }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            AsonValue::Boolean(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut HashMap<String, AsonValue>> {
+        match self {
+            AsonValue::Object(v) => Some(v),
+            _ => None,
+        }
+    }
 
-        let mut parser = Parser::new(unsafe{&*tokens}, "test.json".into());
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<AsonValue>> {
+        match self {
+            AsonValue::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&AsonValue> {
+        self.as_object()?.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut AsonValue> {
+        self.as_object_mut()?.get_mut(key)
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<&AsonValue> {
+        self.as_array()?.get(index)
+    }
+
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut AsonValue> {
+        self.as_array_mut()?.get_mut(index)
+    }
+
+    /// Looks up a value by a JSON pointer (RFC 6901), e.g. `/hobbies/0`.
+    pub fn pointer(&self, pointer: &str) -> Option<&AsonValue> {
+        let mut target = self;
+        for token in pointer_tokens(pointer)? {
+            target = match target {
+                AsonValue::Object(m) => m.get(&token)?,
+                AsonValue::Array(a) => a.get(token.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut AsonValue> {
+        let mut target = self;
+        for token in pointer_tokens(pointer)? {
+            target = match target {
+                AsonValue::Object(m) => m.get_mut(&token)?,
+                AsonValue::Array(a) => a.get_mut(token.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+
+    pub fn from_ason_string(s: &str) -> ParserResult<AsonValue> {
+        Self::from_ason_string_in(s, &mut Environment::new())
+    }
+
+    /// Evaluates `s` against `env`, so callers can pick the limits and
+    /// pre-defined symbols the document runs with.
+    pub fn from_ason_string_in(s: &str, env: &mut Environment) -> ParserResult<AsonValue> {
+        env.reset_budget();
+        Self::evaluate(s, "<string>", env)
+    }
+
+    /// Like `from_ason_string_in`, but `include` and `import` inside the
+    /// document resolve relative to `path`.
+    pub fn from_ason_file_in(s: &str, path: &Path, env: &mut Environment) -> ParserResult<AsonValue> {
+        env.reset_budget();
+        env.push_file(path);
+        let result = Self::evaluate(s, &path.display().to_string(), env);
+        env.pop_file();
+        result
+    }
+
+    /// Evaluates every top-level value in `s` in turn, like lines of NDJSON.
+    pub fn documents_in<'a>(s: &'a str, env: &'a mut Environment) -> Documents<'a> {
+        Documents::new(s, None, env)
+    }
+
+    /// Like `documents_in`, resolving `include` and `import` relative to `path`.
+    pub fn file_documents_in<'a>(s: &'a str, path: &Path, env: &'a mut Environment) -> Documents<'a> {
+        Documents::new(s, Some(path), env)
+    }
+
+    pub(crate) fn evaluate(s: &str, file: &str, env: &mut Environment) -> ParserResult<AsonValue> {
+        let tokens = tokenize(s, file)?;
+        let mut parser = Parser::new(&tokens, file.into(), env);
         parser.parse()
     }
 
+    pub fn from_json_string(s: &str) -> JsonResult<AsonValue> {
+        JsonReader::new(s).read()
+    }
+
     pub fn to_json(&self) -> String {
         match self {
-            AsonValue::Function(_) => "".into(),
+            AsonValue::Function(_) => "null".into(),
             AsonValue::Object(m) => {
-                let mut json = "{".to_string();
-                for (k, v) in m {
-                    json.push_str(&format!("\"{}\":{},", k, v.to_json()));
-                }
-                json.pop();
-                json.push('}');
-                json
+                let members: Vec<String> = m
+                    .iter()
+                    .map(|(k, v)| format!("{}:{}", json_string(k), v.to_json()))
+                    .collect();
+                format!("{{{}}}", members.join(","))
             },
             AsonValue::Array(a) => {
-                let mut json = "[".to_string();
-                for v in a {
-                    json.push_str(&format!("{},", v.to_json()));
-                }
-                json.pop();
-                json.push(']');
-                json
+                let elements: Vec<String> = a.iter().map(|v| v.to_json()).collect();
+                format!("[{}]", elements.join(","))
             },
-            AsonValue::String(s) => format!("\"{}\"", s),
-            AsonValue::Number(n) => n.to_string(),
+            AsonValue::String(s) => json_string(s),
+            AsonValue::Number(n) => json_number(n),
             AsonValue::Boolean(b) => b.to_string(),
             AsonValue::Null => "null".to_string(),
         }
     }
 
-    // to_pretty_json method
-    pub fn to_pretty_json(&self, indent: usize) -> String {
+    /// Writes the value back as ASON source that evaluates to the same value.
+    pub fn to_ason(&self) -> String {
         match self {
-            AsonValue::Function(_) => "".into(),
+            AsonValue::Function(_) => "null".into(),
             AsonValue::Object(m) => {
-                let mut json = "{\n".to_string();
+                let mut ason = "}".to_string();
                 for (k, v) in m {
-                    json.push_str(&format!("{}\"{}\": {},\n", " ".repeat(indent), k, v.to_pretty_json(indent + 2)));
+                    ason.push_str(&format!(" {} {}", ason_string(k), v.to_ason()));
                 }
-                json.pop();
-                json.push_str("\n}");
-                json
+                ason.push_str(" {");
+                ason
             },
             AsonValue::Array(a) => {
-                let mut json = "[\n".to_string();
+                let mut ason = "]".to_string();
                 for v in a {
-                    json.push_str(&format!("{}{},\n", " ".repeat(indent), v.to_pretty_json(indent + 2)));
+                    ason.push_str(&format!(" {}", v.to_ason()));
                 }
-                json.pop();
-                json.push_str("\n]");
-                json
+                ason.push_str(" [");
+                ason
             },
-            AsonValue::String(s) => format!("\"{}\"", s),
-            AsonValue::Number(n) => n.to_string(),
+            AsonValue::String(s) => ason_string(s),
+            AsonValue::Number(n) => ason_number(n),
             AsonValue::Boolean(b) => b.to_string(),
             AsonValue::Null => "null".to_string(),
         }
     }
+
+    // to_pretty_json method
+    pub fn to_pretty_json(&self, indent: usize) -> String {
+        let closing = " ".repeat(indent.saturating_sub(2));
+        match self {
+            AsonValue::Object(m) if m.is_empty() => "{}".into(),
+            AsonValue::Object(m) => {
+                let members: Vec<String> = m
+                    .iter()
+                    .map(|(k, v)| format!("{}{}: {}", " ".repeat(indent), json_string(k), v.to_pretty_json(indent + 2)))
+                    .collect();
+                format!("{{\n{}\n{}}}", members.join(",\n"), closing)
+            },
+            AsonValue::Array(a) if a.is_empty() => "[]".into(),
+            AsonValue::Array(a) => {
+                let elements: Vec<String> = a
+                    .iter()
+                    .map(|v| format!("{}{}", " ".repeat(indent), v.to_pretty_json(indent + 2)))
+                    .collect();
+                format!("[\n{}\n{}]", elements.join(",\n"), closing)
+            },
+            _ => self.to_json(),
+        }
+    }
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut result = "\"".to_string();
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn json_number(n: &AsonNumber) -> String {
+    match n {
+        AsonNumber::Float(f) if !f.is_finite() => "null".into(),
+        n => n.to_string(),
+    }
+}
+
+/// Splits a JSON pointer into its unescaped reference tokens.
+pub(crate) fn pointer_tokens(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+    let rest = pointer.strip_prefix('/')?;
+    Some(rest.split('/').map(|t| t.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+fn ason_string(s: &str) -> String {
+    let mut result = "\"".to_string();
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => result.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+// There are no negative literals, so negative numbers are written as a
+// subtraction.
+fn ason_number(n: &AsonNumber) -> String {
+    match *n {
+        AsonNumber::Integer(i64::MIN) => format!(")0 {} 1 -(", i64::MAX),
+        AsonNumber::Integer(i) if i < 0 => format!(")0 {} -(", -i),
+        AsonNumber::Integer(i) => i.to_string(),
+        AsonNumber::Float(f) if !f.is_finite() => "null".into(),
+        AsonNumber::Float(f) => {
+            let mut literal = f.abs().to_string();
+            if !literal.contains('.') {
+                literal.push_str(".0");
+            }
+            if f.is_sign_negative() {
+                format!(")0.0 {} -(", literal)
+            } else {
+                literal
+            }
+        }
+    }
 }
 
 impl fmt::Display for AsonValue {
@@ -281,44 +533,30 @@ impl fmt::Display for AsonValue {
                 write!(f, "]")
             },
             AsonValue::String(s) => write!(f, "\"{}\"", s),
-            AsonValue::Number(n) => write!(f, "{}", n.to_string()),
+            AsonValue::Number(n) => write!(f, "{}", n),
             AsonValue::Boolean(b) => write!(f, "{}", b),
             AsonValue::Null => write!(f, "null"),
         }
     }
 }
 
+static NULL: AsonValue = AsonValue::Null;
+
+/// Missing members and non-object values index to `null`.
 impl ops::Index<&str> for AsonValue {
     type Output = AsonValue;
 
     fn index(&self, index: &str) -> &Self::Output {
-        match self {
-            AsonValue::Object(m) => {
-                if m.contains_key(index) {
-                    &m[index]
-                } else {
-                    panic!("member with key '{}' is not found.", index);
-                }
-            },
-            _ => panic!("cannot index with a string on a non-object value.")
-        }
+        self.get(index).unwrap_or(&NULL)
     }
 }
 
+/// Out of range indices and non-array values index to `null`.
 impl ops::Index<usize> for AsonValue {
     type Output = AsonValue;
 
     fn index(&self, index: usize) -> &Self::Output {
-        match self {
-            AsonValue::Array(a) => {
-                if index < a.len() {
-                    &a[index]
-                } else {
-                    panic!("Index out of range.")
-                }
-            }
-            _ => panic!("cannot index with a number on a non-array value"),
-        }
+        self.get_index(index).unwrap_or(&NULL)
     }
 }
 
@@ -352,10 +590,10 @@ impl From<bool> for AsonValue {
     }
 }
 
-impl<T: Into<AsonValue>> Into<AsonValue> for Vec<T> {
-    fn into(self) -> AsonValue {
+impl<T: Into<AsonValue>> From<Vec<T>> for AsonValue {
+    fn from(value: Vec<T>) -> Self {
         AsonValue::Array(
-            self
+            value
                 .into_iter()
                 .map(|v| v.into())
                 .collect()
@@ -363,10 +601,10 @@ impl<T: Into<AsonValue>> Into<AsonValue> for Vec<T> {
     }
 }
 
-impl<T: Into<AsonValue>> Into<AsonValue> for HashMap<String, T> {
-    fn into(self) -> AsonValue {
+impl<T: Into<AsonValue>> From<HashMap<String, T>> for AsonValue {
+    fn from(value: HashMap<String, T>) -> Self {
         AsonValue::Object(
-            self
+            value
                 .into_iter()
                 .map(|(k, v)| (k, v.into()))
                 .collect()
@@ -0,0 +1,464 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::ast::{AsonNumber, AsonValue};
+use crate::limits::Limits;
+
+#[derive(Debug)]
+pub struct BinaryError {
+    msg: String,
+    offset: usize,
+}
+
+impl fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "byte {}: {}", self.offset, self.msg)
+    }
+}
+
+pub type BinaryResult<T> = Result<T, BinaryError>;
+
+/// Encodes `value` as MessagePack. Integers use the smallest int format that
+/// holds them and floats are always float 64, so decoding gives back the same
+/// `AsonNumber` variants. Functions are written as nil.
+pub fn to_msgpack(value: &AsonValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_msgpack(value, &mut out);
+    out
+}
+
+/// Decodes one MessagePack value, which must be all of `bytes`.
+pub fn from_msgpack(bytes: &[u8]) -> BinaryResult<AsonValue> {
+    Reader::new(bytes).read(Reader::msgpack)
+}
+
+/// Encodes `value` as CBOR (RFC 8949), with integers in major types 0 and 1
+/// and floats always as float 64. Functions are written as null.
+pub fn to_cbor(value: &AsonValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_cbor(value, &mut out);
+    out
+}
+
+/// Decodes one CBOR value, which must be all of `bytes`. Tags are ignored,
+/// and `undefined` reads as null.
+pub fn from_cbor(bytes: &[u8]) -> BinaryResult<AsonValue> {
+    Reader::new(bytes).read(Reader::cbor)
+}
+
+fn write_msgpack(value: &AsonValue, out: &mut Vec<u8>) {
+    match value {
+        AsonValue::Null | AsonValue::Function(_) => out.push(0xc0),
+        AsonValue::Boolean(b) => out.push(if *b { 0xc3 } else { 0xc2 }),
+        AsonValue::Number(AsonNumber::Integer(i)) => match *i {
+            0..=0x7f => out.push(*i as u8),
+            -32..=-1 => out.push(*i as u8),
+            0x80..=0xff => out.extend([0xcc, *i as u8]),
+            0x100..=0xffff => {
+                out.push(0xcd);
+                out.extend((*i as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                out.push(0xce);
+                out.extend((*i as u32).to_be_bytes());
+            }
+            0x1_0000_0000.. => {
+                out.push(0xcf);
+                out.extend((*i as u64).to_be_bytes());
+            }
+            -0x80..=-33 => out.extend([0xd0, *i as u8]),
+            -0x8000..=-0x81 => {
+                out.push(0xd1);
+                out.extend((*i as i16).to_be_bytes());
+            }
+            -0x8000_0000..=-0x8001 => {
+                out.push(0xd2);
+                out.extend((*i as i32).to_be_bytes());
+            }
+            _ => {
+                out.push(0xd3);
+                out.extend(i.to_be_bytes());
+            }
+        },
+        AsonValue::Number(AsonNumber::Float(f)) => {
+            out.push(0xcb);
+            out.extend(f.to_be_bytes());
+        }
+        AsonValue::String(s) => {
+            msgpack_header(out, s.len(), 0xa0, 32, [0xd9, 0xda, 0xdb]);
+            out.extend(s.as_bytes());
+        }
+        AsonValue::Array(a) => {
+            msgpack_header(out, a.len(), 0x90, 16, [0, 0xdc, 0xdd]);
+            a.iter().for_each(|v| write_msgpack(v, out));
+        }
+        AsonValue::Object(m) => {
+            msgpack_header(out, m.len(), 0x80, 16, [0, 0xde, 0xdf]);
+            for (k, v) in m {
+                write_msgpack(&AsonValue::String(k.clone()), out);
+                write_msgpack(v, out);
+            }
+        }
+    }
+}
+
+/// Writes a length as the fix format when under `fix_limit`, otherwise with
+/// the 8 (when it exists), 16 or 32 bit format.
+fn msgpack_header(out: &mut Vec<u8>, len: usize, fix: u8, fix_limit: usize, formats: [u8; 3]) {
+    if len < fix_limit {
+        out.push(fix | len as u8);
+    } else if len <= 0xff && formats[0] != 0 {
+        out.extend([formats[0], len as u8]);
+    } else if len <= 0xffff {
+        out.push(formats[1]);
+        out.extend((len as u16).to_be_bytes());
+    } else {
+        out.push(formats[2]);
+        out.extend((len as u32).to_be_bytes());
+    }
+}
+
+fn write_cbor(value: &AsonValue, out: &mut Vec<u8>) {
+    match value {
+        AsonValue::Null | AsonValue::Function(_) => out.push(0xf6),
+        AsonValue::Boolean(b) => out.push(if *b { 0xf5 } else { 0xf4 }),
+        AsonValue::Number(AsonNumber::Integer(i)) if *i >= 0 => cbor_header(out, 0, *i as u64),
+        // -1 - n, which is the bitwise not of n.
+        AsonValue::Number(AsonNumber::Integer(i)) => cbor_header(out, 1, !*i as u64),
+        AsonValue::Number(AsonNumber::Float(f)) => {
+            out.push(0xfb);
+            out.extend(f.to_be_bytes());
+        }
+        AsonValue::String(s) => {
+            cbor_header(out, 3, s.len() as u64);
+            out.extend(s.as_bytes());
+        }
+        AsonValue::Array(a) => {
+            cbor_header(out, 4, a.len() as u64);
+            a.iter().for_each(|v| write_cbor(v, out));
+        }
+        AsonValue::Object(m) => {
+            cbor_header(out, 5, m.len() as u64);
+            for (k, v) in m {
+                write_cbor(&AsonValue::String(k.clone()), out);
+                write_cbor(v, out);
+            }
+        }
+    }
+}
+
+fn cbor_header(out: &mut Vec<u8>, major: u8, argument: u64) {
+    let major = major << 5;
+    match argument {
+        0..=23 => out.push(major | argument as u8),
+        24..=0xff => out.extend([major | 24, argument as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend((argument as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend((argument as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend(argument.to_be_bytes());
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, offset: 0, depth: 0, max_depth: Limits::default().max_depth }
+    }
+
+    fn error<T>(&self, msg: String) -> BinaryResult<T> {
+        Err(BinaryError { msg, offset: self.offset })
+    }
+
+    fn read(&mut self, value: fn(&mut Self) -> BinaryResult<AsonValue>) -> BinaryResult<AsonValue> {
+        let value = value(self)?;
+        if self.offset < self.bytes.len() {
+            return self.error("Unexpected bytes after the value.".into());
+        }
+        Ok(value)
+    }
+
+    fn take(&mut self, len: usize) -> BinaryResult<&'a [u8]> {
+        match self.bytes.get(self.offset..).and_then(|rest| rest.get(..len)) {
+            Some(bytes) => {
+                self.offset += len;
+                Ok(bytes)
+            }
+            None => self.error("Unexpected end of input.".into()),
+        }
+    }
+
+    fn byte(&mut self) -> BinaryResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> BinaryResult<[u8; N]> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn string(&mut self, len: usize) -> BinaryResult<String> {
+        let start = self.offset;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).or_else(|_| {
+            self.offset = start;
+            self.error("Strings must be UTF-8.".into())
+        })
+    }
+
+    fn integer(&self, n: u64) -> BinaryResult<AsonValue> {
+        match i64::try_from(n) {
+            Ok(i) => Ok(AsonValue::from(i)),
+            Err(_) => self.error(format!("{} is too large for a 64 bit integer.", n)),
+        }
+    }
+
+    fn enter(&mut self) -> BinaryResult<()> {
+        self.depth += 1;
+        if self.depth > self.max_depth {
+            return self.error(format!("Nesting deeper than {} levels.", self.max_depth));
+        }
+        Ok(())
+    }
+
+    fn msgpack(&mut self) -> BinaryResult<AsonValue> {
+        let start = self.offset;
+        let marker = self.byte()?;
+        let value = match marker {
+            0x00..=0x7f => AsonValue::from(marker as i64),
+            0x80..=0x8f => return self.msgpack_map(marker as usize & 0x0f),
+            0x90..=0x9f => return self.msgpack_array(marker as usize & 0x0f),
+            0xa0..=0xbf => AsonValue::String(self.string(marker as usize & 0x1f)?),
+            0xc0 => AsonValue::Null,
+            0xc2 => AsonValue::Boolean(false),
+            0xc3 => AsonValue::Boolean(true),
+            0xca => AsonValue::from(f32::from_be_bytes(self.array()?) as f64),
+            0xcb => AsonValue::from(f64::from_be_bytes(self.array()?)),
+            0xcc => AsonValue::from(self.byte()? as i64),
+            0xcd => AsonValue::from(u16::from_be_bytes(self.array()?) as i64),
+            0xce => AsonValue::from(u32::from_be_bytes(self.array()?) as i64),
+            0xcf => {
+                let n = u64::from_be_bytes(self.array()?);
+                self.integer(n)?
+            }
+            0xd0 => AsonValue::from(self.byte()? as i8 as i64),
+            0xd1 => AsonValue::from(i16::from_be_bytes(self.array()?) as i64),
+            0xd2 => AsonValue::from(i32::from_be_bytes(self.array()?) as i64),
+            0xd3 => AsonValue::from(i64::from_be_bytes(self.array()?)),
+            0xd9 => {
+                let len = self.byte()? as usize;
+                AsonValue::String(self.string(len)?)
+            }
+            0xda => {
+                let len = u16::from_be_bytes(self.array()?) as usize;
+                AsonValue::String(self.string(len)?)
+            }
+            0xdb => {
+                let len = u32::from_be_bytes(self.array()?) as usize;
+                AsonValue::String(self.string(len)?)
+            }
+            0xdc => {
+                let len = u16::from_be_bytes(self.array()?) as usize;
+                return self.msgpack_array(len);
+            }
+            0xdd => {
+                let len = u32::from_be_bytes(self.array()?) as usize;
+                return self.msgpack_array(len);
+            }
+            0xde => {
+                let len = u16::from_be_bytes(self.array()?) as usize;
+                return self.msgpack_map(len);
+            }
+            0xdf => {
+                let len = u32::from_be_bytes(self.array()?) as usize;
+                return self.msgpack_map(len);
+            }
+            0xe0..=0xff => AsonValue::from(marker as i8 as i64),
+            0xc4..=0xc6 => {
+                self.offset = start;
+                return self.error("Binary data has no ASON value.".into());
+            }
+            0xc7..=0xc9 | 0xd4..=0xd8 => {
+                self.offset = start;
+                return self.error("Extension types have no ASON value.".into());
+            }
+            0xc1 => {
+                self.offset = start;
+                return self.error("0xc1 is never used in MessagePack.".into());
+            }
+        };
+        Ok(value)
+    }
+
+    fn msgpack_array(&mut self, len: usize) -> BinaryResult<AsonValue> {
+        self.enter()?;
+        let mut elements = Vec::new();
+        for _ in 0..len {
+            elements.push(self.msgpack()?);
+        }
+        self.depth -= 1;
+        Ok(AsonValue::Array(elements))
+    }
+
+    fn msgpack_map(&mut self, len: usize) -> BinaryResult<AsonValue> {
+        self.enter()?;
+        let mut members = HashMap::new();
+        for _ in 0..len {
+            let start = self.offset;
+            let AsonValue::String(key) = self.msgpack()? else {
+                self.offset = start;
+                return self.error("Map keys must be strings.".into());
+            };
+            members.insert(key, self.msgpack()?);
+        }
+        self.depth -= 1;
+        Ok(AsonValue::Object(members))
+    }
+
+    fn cbor(&mut self) -> BinaryResult<AsonValue> {
+        let start = self.offset;
+        let initial = self.byte()?;
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        if info == 31 {
+            return match major {
+                3 => self.cbor_chunks(start),
+                4 => self.cbor_array(None),
+                5 => self.cbor_map(None),
+                _ => {
+                    self.offset = start;
+                    self.error(format!("Unexpected indefinite length for major type {}.", major))
+                }
+            };
+        }
+        if major == 7 {
+            return match info {
+                20 => Ok(AsonValue::Boolean(false)),
+                21 => Ok(AsonValue::Boolean(true)),
+                22 | 23 => Ok(AsonValue::Null),
+                25 => Ok(AsonValue::from(f16_to_f64(u16::from_be_bytes(self.array()?)))),
+                26 => Ok(AsonValue::from(f32::from_be_bytes(self.array()?) as f64)),
+                27 => Ok(AsonValue::from(f64::from_be_bytes(self.array()?))),
+                _ => {
+                    self.offset = start;
+                    self.error(format!("Simple value {} has no ASON value.", info))
+                }
+            };
+        }
+        let argument = match info {
+            0..=23 => info as u64,
+            24 => self.byte()? as u64,
+            25 => u16::from_be_bytes(self.array()?) as u64,
+            26 => u32::from_be_bytes(self.array()?) as u64,
+            27 => u64::from_be_bytes(self.array()?),
+            _ => {
+                self.offset = start;
+                return self.error(format!("Reserved additional information {}.", info));
+            }
+        };
+        match major {
+            0 => self.integer(argument),
+            1 => match self.integer(argument)? {
+                AsonValue::Number(AsonNumber::Integer(n)) => Ok(AsonValue::from(-1 - n)),
+                _ => unreachable!(),
+            },
+            2 => {
+                self.offset = start;
+                self.error("Byte strings have no ASON value.".into())
+            }
+            3 => Ok(AsonValue::String(self.string(argument as usize)?)),
+            4 => self.cbor_array(Some(argument)),
+            5 => self.cbor_map(Some(argument)),
+            // Tags only add meaning to the value that follows.
+            _ => {
+                self.enter()?;
+                let value = self.cbor()?;
+                self.depth -= 1;
+                Ok(value)
+            }
+        }
+    }
+
+    /// Reads the definite length text strings of an indefinite length one.
+    fn cbor_chunks(&mut self, start: usize) -> BinaryResult<AsonValue> {
+        let mut text = String::new();
+        while self.bytes.get(self.offset) != Some(&0xff) {
+            match self.bytes.get(self.offset) {
+                Some(initial) if initial >> 5 == 3 && initial & 0x1f != 31 => match self.cbor()? {
+                    AsonValue::String(chunk) => text.push_str(&chunk),
+                    _ => unreachable!(),
+                },
+                Some(_) => return self.error("Indefinite length strings hold text strings only.".into()),
+                None => {
+                    self.offset = start;
+                    return self.error("Unterminated indefinite length string.".into());
+                }
+            }
+        }
+        self.offset += 1;
+        Ok(AsonValue::String(text))
+    }
+
+    /// `None` is an indefinite length, which ends at a 0xff break.
+    fn at_end(&mut self, len: Option<u64>, read: u64) -> BinaryResult<bool> {
+        match len {
+            Some(len) => Ok(read == len),
+            None if self.bytes.get(self.offset) == Some(&0xff) => {
+                self.offset += 1;
+                Ok(true)
+            }
+            None if self.offset >= self.bytes.len() => self.error("Unexpected end of input.".into()),
+            None => Ok(false),
+        }
+    }
+
+    fn cbor_array(&mut self, len: Option<u64>) -> BinaryResult<AsonValue> {
+        self.enter()?;
+        let mut elements = Vec::new();
+        while !self.at_end(len, elements.len() as u64)? {
+            elements.push(self.cbor()?);
+        }
+        self.depth -= 1;
+        Ok(AsonValue::Array(elements))
+    }
+
+    fn cbor_map(&mut self, len: Option<u64>) -> BinaryResult<AsonValue> {
+        self.enter()?;
+        let mut members = HashMap::new();
+        let mut read = 0;
+        while !self.at_end(len, read)? {
+            let start = self.offset;
+            let AsonValue::String(key) = self.cbor()? else {
+                self.offset = start;
+                return self.error("Map keys must be strings.".into());
+            };
+            members.insert(key, self.cbor()?);
+            read += 1;
+        }
+        self.depth -= 1;
+        Ok(AsonValue::Object(members))
+    }
+}
+
+fn f16_to_f64(half: u16) -> f64 {
+    let exponent = (half >> 10) & 0x1f;
+    let mantissa = (half & 0x3ff) as f64;
+    let magnitude = match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f64.powi(exponent as i32 - 15),
+    };
+    if half & 0x8000 != 0 { -magnitude } else { magnitude }
+}
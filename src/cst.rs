@@ -0,0 +1,493 @@
+use core::fmt;
+use std::ops::Range;
+
+use crate::ast::{pointer_tokens, AsonValue};
+use crate::lexer::Lexer;
+use crate::limits::Limits;
+use crate::span::Span;
+use crate::token::{Token, TokenKind, Trivia, TriviaKind};
+
+#[derive(Debug)]
+pub struct CstError {
+    msg: String,
+}
+
+impl fmt::Display for CstError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+pub type CstResult<T> = Result<T, CstError>;
+
+fn error<T>(msg: String) -> CstResult<T> {
+    Err(CstError { msg })
+}
+
+#[derive(Debug, Clone)]
+pub enum NodeKind {
+    /// `commas` tells whether the members are separated with `,`.
+    Object { members: Vec<Member>, commas: bool },
+    Array { elements: Vec<Element>, commas: bool },
+    /// A literal or a symbol.
+    Value,
+    /// An expression-s, kept as it is written.
+    Expr,
+    /// `...value` inside an object or an array.
+    Spread(Box<Node>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub span: Span,
+}
+
+/// What a piece of the source is. See `Document::pieces`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PieceKind {
+    Token,
+    Whitespace,
+    Comment(TriviaKind),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Piece {
+    pub kind: PieceKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Member {
+    /// Empty for keys computed by a symbol or an expression-s, and for spreads.
+    pub key: String,
+    /// The `...` of a spread.
+    pub key_span: Span,
+    pub computed: bool,
+    /// A `...value` member, whose value is a `Spread` node.
+    pub spread: bool,
+    /// Where the member starts, including the comments above it.
+    pub start: usize,
+    /// The `\\\` doc-comments above the member, without their markers.
+    pub doc: Option<String>,
+    pub colon: bool,
+    pub value: Node,
+    pub comma: Option<Span>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Element {
+    pub value: Node,
+    pub comma: Option<Span>,
+}
+
+/// A lossless view of an ASON document. Whitespace and comments live in the
+/// source between the nodes' spans, so printing the document gives back the
+/// exact text it was parsed from, and edits only touch the region they change.
+/// `pieces` lists them alongside the tokens for tools that need every byte.
+#[derive(Debug, Clone)]
+pub struct Document {
+    source: String,
+    root: Node,
+}
+
+impl Member {
+    /// Whether this member is written with the literal key `key`.
+    pub fn is(&self, key: &str) -> bool {
+        !self.computed && !self.spread && self.key == key
+    }
+}
+
+impl Document {
+    pub fn parse(source: &str) -> CstResult<Document> {
+        let root = build(source)?;
+        Ok(Document { source: source.to_string(), root })
+    }
+
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Sets `key` in the object at `pointer`, replacing the value of an
+    /// existing member or adding a new one after the last member.
+    pub fn set_member(&mut self, pointer: &str, key: &str, value: &AsonValue) -> CstResult<()> {
+        let node = self.resolve(pointer)?;
+        let NodeKind::Object { members, commas } = &node.kind else {
+            return error(format!("'{}' is not an object", pointer));
+        };
+        if let Some(member) = members.iter().rev().find(|m| m.is(key)) {
+            let span = member.value.span;
+            return self.splice(span.start..span.end, &value.to_ason());
+        }
+
+        let key = AsonValue::from(key).to_ason();
+        let colon = if members.iter().any(|m| m.colon) { ":" } else { "" };
+        let member = format!("{}{} {}", key, colon, value.to_ason());
+        match members.last() {
+            Some(last) => {
+                let multiline = self.multiline(node, members[0].start);
+                self.append(last.start..last.value.span.end, last.comma, *commas, multiline, &member)
+            }
+            None => self.insert_into_empty(node.span, &member),
+        }
+    }
+
+    /// Removes every member named `key` from the object at `pointer`, along
+    /// with its comments and, when it sat on lines of its own, those lines.
+    pub fn remove_member(&mut self, pointer: &str, key: &str) -> CstResult<()> {
+        let node = self.resolve(pointer)?;
+        let NodeKind::Object { members, .. } = &node.kind else {
+            return error(format!("'{}' is not an object", pointer));
+        };
+        let ranges: Vec<Range<usize>> = members
+            .iter()
+            .filter(|m| m.is(key))
+            .map(|m| self.removal(m.start, m.comma.map_or(m.value.span.end, |c| c.end)))
+            .collect();
+        if ranges.is_empty() {
+            return error(format!("'{}' has no member \"{}\"", pointer, key));
+        }
+        let mut source = self.source.clone();
+        for range in ranges.into_iter().rev() {
+            source.replace_range(range, "");
+        }
+        self.replace(source)
+    }
+
+    /// Inserts `value` before the element at `index` of the array at
+    /// `pointer`, or after the last one when `index` is its length.
+    pub fn insert_array_element(&mut self, pointer: &str, index: usize, value: &AsonValue) -> CstResult<()> {
+        let node = self.resolve(pointer)?;
+        let NodeKind::Array { elements, commas } = &node.kind else {
+            return error(format!("'{}' is not an array", pointer));
+        };
+        if index > elements.len() {
+            return error(format!("index {} is out of bounds for '{}'", index, pointer));
+        }
+
+        let value = value.to_ason();
+        match (elements.get(index), elements.last()) {
+            (Some(element), _) => {
+                let at = element.value.span.start;
+                let comma = if *commas { "," } else { "" };
+                let space = if self.multiline(node, elements[0].value.span.start) {
+                    format!("\n{}", self.indent(at))
+                } else {
+                    " ".to_string()
+                };
+                self.splice(at..at, &format!("{}{}{}", value, comma, space))
+            }
+            (None, Some(last)) => {
+                let multiline = self.multiline(node, elements[0].value.span.start);
+                let span = last.value.span;
+                self.append(span.start..span.end, last.comma, *commas, multiline, &value)
+            }
+            (None, None) => self.insert_into_empty(node.span, &value),
+        }
+    }
+
+    /// The span of the value at `pointer`, or of its closest ancestor
+    /// written in the source when the value itself isn't, like one coming
+    /// from a spread or a computed key.
+    pub fn span(&self, pointer: &str) -> Span {
+        let mut node = &self.root;
+        for token in pointer_tokens(pointer).unwrap_or_default() {
+            // Spreads and computed keys can replace members and move elements.
+            let next = match &node.kind {
+                NodeKind::Object { members, .. } => members
+                    .iter()
+                    .rposition(|m| m.is(&token))
+                    .filter(|&i| !members[i..].iter().any(|m| m.spread || m.computed))
+                    .map(|i| &members[i].value),
+                NodeKind::Array { elements, .. } => token
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&i| i < elements.len() && !elements[..=i].iter().any(|e| matches!(e.value.kind, NodeKind::Spread(_))))
+                    .map(|i| &elements[i].value),
+                _ => None,
+            };
+            match next {
+                Some(next) => node = next,
+                None => break,
+            }
+        }
+        node.span
+    }
+
+    /// Every token, comment and run of whitespace of the source, in order.
+    /// Their spans cover the whole text without gaps.
+    pub fn pieces(&self) -> Vec<Piece> {
+        let mut lex = Lexer::new(&self.source);
+        // The source was scanned when it was parsed, so this can't fail.
+        let _ = lex.scan();
+        let mut spans: Vec<Piece> = Vec::new();
+        for token in lex.into_tokens() {
+            let comments = |trivia: &[Trivia]| trivia.iter().map(|t| Piece { kind: PieceKind::Comment(t.kind), span: t.span }).collect::<Vec<_>>();
+            spans.extend(comments(&token.leading));
+            spans.push(Piece { kind: PieceKind::Token, span: token.span });
+            spans.extend(comments(&token.trailing));
+        }
+        spans.sort_by_key(|p| p.span.start);
+
+        let mut pieces = Vec::new();
+        let mut at = 0;
+        for piece in spans {
+            if piece.span.start > at {
+                pieces.push(Piece { kind: PieceKind::Whitespace, span: Span::new(at, piece.span.start) });
+            }
+            at = piece.span.end;
+            pieces.push(piece);
+        }
+        if at < self.source.len() {
+            pieces.push(Piece { kind: PieceKind::Whitespace, span: Span::new(at, self.source.len()) });
+        }
+        pieces
+    }
+
+    fn resolve(&self, pointer: &str) -> CstResult<&Node> {
+        let Some(tokens) = pointer_tokens(pointer) else {
+            return error(format!("'{}' is not a JSON pointer", pointer));
+        };
+        let mut node = &self.root;
+        for token in tokens {
+            let next = match &node.kind {
+                NodeKind::Object { members, .. } => members.iter().rev().find(|m| m.is(&token)).map(|m| &m.value),
+                NodeKind::Array { elements, .. } => token.parse::<usize>().ok().and_then(|i| elements.get(i)).map(|e| &e.value),
+                _ => None,
+            };
+            node = match next {
+                Some(next) => next,
+                None => return error(format!("'{}' does not exist", pointer)),
+            };
+        }
+        Ok(node)
+    }
+
+    /// Adds `item` after the last child, which spans `last`. On multi-line
+    /// containers it goes on a new line, after any comment ending the last one.
+    fn append(&mut self, last: Range<usize>, comma: Option<Span>, commas: bool, multiline: bool, item: &str) -> CstResult<()> {
+        let mut at = comma.map_or(last.end, |c| c.end);
+        let mut text = String::new();
+        if multiline {
+            let line_end = self.source[at..].find('\n').map_or(self.source.len(), |i| at + i);
+            let rest = self.source[at..line_end].trim();
+            if rest.is_empty() || rest.starts_with('\\') {
+                at = line_end;
+            }
+            text.push('\n');
+            text.push_str(self.indent(last.start));
+        } else {
+            text.push(' ');
+        }
+        text.push_str(item);
+        if comma.is_some() {
+            text.push(',');
+        }
+
+        let mut source = self.source.clone();
+        source.insert_str(at, &text);
+        if commas && comma.is_none() {
+            source.insert(last.end, ',');
+        }
+        self.replace(source)
+    }
+
+    fn insert_into_empty(&mut self, span: Span, item: &str) -> CstResult<()> {
+        let at = span.start + 1;
+        let space = if self.source[at..].starts_with(char::is_whitespace) { "" } else { " " };
+        self.splice(at..at, &format!(" {}{}", item, space))
+    }
+
+    /// Whether the first child, starting at `first`, is on a later line than the opening bracket.
+    fn multiline(&self, node: &Node, first: usize) -> bool {
+        self.source[node.span.start..first].contains('\n')
+    }
+
+    /// The whitespace at the start of the line containing `at`.
+    fn indent(&self, at: usize) -> &str {
+        let line_start = self.source[..at].rfind('\n').map_or(0, |i| i + 1);
+        let line = &self.source[line_start..];
+        &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+    }
+
+    /// The range to delete for a child spanning `start..end`: whole lines
+    /// when nothing else shares them, otherwise the child and the spaces after it.
+    fn removal(&self, start: usize, end: usize) -> Range<usize> {
+        let line_start = self.source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let rest = &self.source[end..];
+        let line_end = rest.find('\n').map_or(self.source.len(), |i| end + i + 1);
+        let after = self.source[end..line_end].trim();
+        if self.source[line_start..start].trim().is_empty() && (after.is_empty() || after.starts_with("\\\\")) {
+            return line_start..line_end;
+        }
+        end_of_spaces(&self.source, start)..end_of_spaces(&self.source, end)
+    }
+
+    fn splice(&mut self, range: Range<usize>, text: &str) -> CstResult<()> {
+        let mut source = self.source.clone();
+        source.replace_range(range, text);
+        self.replace(source)
+    }
+
+    fn replace(&mut self, source: String) -> CstResult<()> {
+        self.root = build(&source)?;
+        self.source = source;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+fn end_of_spaces(s: &str, at: usize) -> usize {
+    at + s[at..].len() - s[at..].trim_start_matches([' ', '\t']).len()
+}
+
+fn build(source: &str) -> CstResult<Node> {
+    let mut lex = Lexer::new(source);
+    if let Err(msg) = lex.scan() {
+        return error(format!("{}:{}: {}", lex.line(), lex.column(), msg));
+    }
+    let tokens = lex.into_tokens();
+    let Some(first) = tokens.first() else {
+        return error("Expected a value.".into());
+    };
+    if first.kind == TokenKind::Spread {
+        return unexpected(first);
+    }
+    let (root, next) = node(&tokens, 0, first, 0)?;
+    match tokens.get(next) {
+        Some(extra) => unexpected(extra),
+        None => Ok(root),
+    }
+}
+
+fn end_of_file<T>(open: &Token) -> CstResult<T> {
+    error(format!("{}:{}: '{}' is never closed", open.line, open.column, open.lexem))
+}
+
+fn unexpected<T>(token: &Token) -> CstResult<T> {
+    error(format!("{}:{}: Unexpected token: {}", token.line, token.column, token.lexem))
+}
+
+/// Builds the node starting at token `i`, returning it with the index after it.
+/// `depth` counts the containers around it, which are bounded like the
+/// evaluator's so that deep input fails instead of overflowing the stack.
+fn node(tokens: &[Token], i: usize, before: &Token, depth: usize) -> CstResult<(Node, usize)> {
+    let Some(open) = tokens.get(i) else {
+        return error(format!("{}:{}: Expected a value after {}", before.line, before.column, before.lexem));
+    };
+    let max_depth = Limits::default().max_depth;
+    if depth >= max_depth {
+        return error(format!("{}:{}: Maximum nesting depth of {} exceeded.", open.line, open.column, max_depth));
+    }
+    let mut j = i + 1;
+    let kind = match open.kind {
+        TokenKind::OpenObject => {
+            let mut members: Vec<Member> = Vec::new();
+            let mut commas = false;
+            loop {
+                let Some(token) = tokens.get(j) else { return end_of_file(open) };
+                j += 1;
+                match &token.kind {
+                    TokenKind::CloseObject => break,
+                    TokenKind::Comma => {
+                        commas = true;
+                        if let Some(member) = members.last_mut().filter(|m| m.comma.is_none()) {
+                            member.comma = Some(token.span);
+                        }
+                    }
+                    TokenKind::StringLiteral(_) | TokenKind::Symbol(_) | TokenKind::OpenExpr => {
+                        let (key, key_span, computed) = match &token.kind {
+                            TokenKind::StringLiteral(key) => (key.clone(), token.span, false),
+                            _ => {
+                                let (key, next) = node(tokens, j - 1, token, depth + 1)?;
+                                j = next;
+                                (String::new(), key.span, true)
+                            }
+                        };
+                        let colon = tokens.get(j).is_some_and(|t| t.kind == TokenKind::Colon);
+                        if colon {
+                            j += 1;
+                        }
+                        let (value, next) = node(tokens, j, &tokens[j - 1], depth + 1)?;
+                        j = next;
+                        let start = token.leading.first().map_or(token.span.start, |t| t.span.start);
+                        members.push(Member { key, key_span, computed, spread: false, start, doc: token.doc(), colon, value, comma: None });
+                    }
+                    TokenKind::Spread => {
+                        let (value, next) = node(tokens, j - 1, token, depth + 1)?;
+                        j = next;
+                        let start = token.leading.first().map_or(token.span.start, |t| t.span.start);
+                        let key_span = token.span;
+                        members.push(Member { key: String::new(), key_span, computed: false, spread: true, start, doc: None, colon: false, value, comma: None });
+                    }
+                    _ => return unexpected(token),
+                }
+            }
+            NodeKind::Object { members, commas }
+        }
+        TokenKind::OpenArray => {
+            let mut elements: Vec<Element> = Vec::new();
+            let mut commas = false;
+            loop {
+                let Some(token) = tokens.get(j) else { return end_of_file(open) };
+                match token.kind {
+                    TokenKind::CloseArray => {
+                        j += 1;
+                        break;
+                    }
+                    TokenKind::Comma => {
+                        j += 1;
+                        commas = true;
+                        if let Some(element) = elements.last_mut().filter(|e| e.comma.is_none()) {
+                            element.comma = Some(token.span);
+                        }
+                    }
+                    _ => {
+                        let (value, next) = node(tokens, j, token, depth + 1)?;
+                        j = next;
+                        elements.push(Element { value, comma: None });
+                    }
+                }
+            }
+            NodeKind::Array { elements, commas }
+        }
+        TokenKind::OpenExpr => {
+            // Nested expression-s count towards the depth like containers do.
+            let mut open_exprs = 1;
+            while open_exprs > 0 {
+                let Some(token) = tokens.get(j) else { return end_of_file(open) };
+                match token.kind {
+                    TokenKind::OpenExpr => {
+                        open_exprs += 1;
+                        if depth + open_exprs > max_depth {
+                            return error(format!("{}:{}: Maximum nesting depth of {} exceeded.", token.line, token.column, max_depth));
+                        }
+                    }
+                    TokenKind::CloseExpr => open_exprs -= 1,
+                    _ => {}
+                }
+                j += 1;
+            }
+            NodeKind::Expr
+        }
+        TokenKind::Spread => {
+            let (value, next) = node(tokens, j, open, depth + 1)?;
+            j = next;
+            NodeKind::Spread(Box::new(value))
+        }
+        TokenKind::CloseObject | TokenKind::CloseArray | TokenKind::CloseExpr | TokenKind::Colon | TokenKind::Comma => {
+            return unexpected(open);
+        }
+        _ => NodeKind::Value,
+    };
+    Ok((Node { kind, span: open.span.to(tokens[j - 1].span) }, j))
+}
@@ -0,0 +1,339 @@
+use core::fmt;
+use std::collections::HashMap;
+use std::collections::hash_map;
+use std::vec;
+
+use serde::de::{self, Deserialize, DeserializeOwned, IntoDeserializer, Visitor};
+
+use crate::ast::{AsonNumber, AsonValue};
+
+#[derive(Debug)]
+pub struct Error {
+    msg: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error { msg: msg.to_string() }
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error { msg: msg.to_string() }
+    }
+}
+
+/// Evaluates the ASON document in `s` and deserializes the resulting value.
+pub fn from_str<T: DeserializeOwned>(s: &str) -> Result<T, Error> {
+    let value = AsonValue::from_ason_string(s).map_err(de::Error::custom)?;
+    from_value(value)
+}
+
+pub fn from_value<T: DeserializeOwned>(value: AsonValue) -> Result<T, Error> {
+    T::deserialize(value)
+}
+
+impl<'de> Deserialize<'de> for AsonValue {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<AsonValue, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = AsonValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "any ASON value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<AsonValue, E> {
+        Ok(AsonValue::Boolean(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<AsonValue, E> {
+        Ok(v.into())
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<AsonValue, E> {
+        match i64::try_from(v) {
+            Ok(i) => Ok(i.into()),
+            Err(_) => Ok((v as f64).into()),
+        }
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<AsonValue, E> {
+        Ok(v.into())
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<AsonValue, E> {
+        Ok(v.into())
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<AsonValue, E> {
+        Ok(v.into())
+    }
+
+    fn visit_none<E>(self) -> Result<AsonValue, E> {
+        Ok(AsonValue::Null)
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<AsonValue, D::Error> {
+        Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> Result<AsonValue, E> {
+        Ok(AsonValue::Null)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<AsonValue, A::Error> {
+        let mut elements = Vec::new();
+        while let Some(v) = seq.next_element()? {
+            elements.push(v);
+        }
+        Ok(AsonValue::Array(elements))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<AsonValue, A::Error> {
+        let mut members = HashMap::new();
+        while let Some((k, v)) = map.next_entry()? {
+            members.insert(k, v);
+        }
+        Ok(AsonValue::Object(members))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for AsonValue {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            AsonValue::Function(_) => Err(de::Error::custom("functions cannot be deserialized")),
+            AsonValue::Object(m) => visitor.visit_map(MapDeserializer { iter: m.into_iter(), value: None }),
+            AsonValue::Array(a) => visitor.visit_seq(SeqDeserializer { iter: a.into_iter() }),
+            AsonValue::String(s) => visitor.visit_string(s),
+            AsonValue::Number(AsonNumber::Integer(i)) => visitor.visit_i64(i),
+            AsonValue::Number(AsonNumber::Float(f)) => visitor.visit_f64(f),
+            AsonValue::Boolean(b) => visitor.visit_bool(b),
+            AsonValue::Null => visitor.visit_unit(),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            AsonValue::Null => visitor.visit_none(),
+            v => visitor.visit_some(v),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self {
+            AsonValue::String(variant) => visitor.visit_enum(EnumDeserializer { variant, value: None }),
+            AsonValue::Object(m) if m.len() == 1 => {
+                let (variant, value) = m.into_iter().next().unwrap();
+                visitor.visit_enum(EnumDeserializer { variant, value: Some(value) })
+            }
+            _ => Err(de::Error::custom("expected a string or an object with a single key for an enum")),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, Error> for AsonValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct SeqDeserializer {
+    iter: vec::IntoIter<AsonValue>,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqDeserializer {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        match self.iter.next() {
+            Some(v) => seed.deserialize(v).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct MapDeserializer {
+    iter: hash_map::IntoIter<String, AsonValue>,
+    value: Option<AsonValue>,
+}
+
+impl<'de> de::MapAccess<'de> for MapDeserializer {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        match self.iter.next() {
+            Some((k, v)) => {
+                self.value = Some(v);
+                seed.deserialize(KeyDeserializer { key: k }).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        match self.value.take() {
+            Some(v) => seed.deserialize(v),
+            None => Err(de::Error::custom("value requested before key")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// Object keys are always strings, but maps like `HashMap<u32, _>` are
+/// written with their keys as text, so those are parsed back.
+struct KeyDeserializer {
+    key: String,
+}
+
+impl KeyDeserializer {
+    fn parse<T: std::str::FromStr>(&self) -> Result<T, Error> {
+        self.key.parse().map_err(|_| de::Error::invalid_value(de::Unexpected::Str(&self.key), &"a key of the expected type"))
+    }
+}
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.key)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        AsonValue::String(self.key).deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Option<AsonValue>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, VariantDeserializer), Error> {
+        let variant = seed.deserialize(AsonValue::String(self.variant))?;
+        Ok((variant, VariantDeserializer { value: self.value }))
+    }
+}
+
+struct VariantDeserializer {
+    value: Option<AsonValue>,
+}
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.value {
+            None | Some(AsonValue::Null) => Ok(()),
+            Some(_) => Err(de::Error::custom("expected a unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        match self.value {
+            Some(v) => seed.deserialize(v),
+            None => Err(de::Error::custom("expected a newtype variant")),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Some(AsonValue::Array(a)) => visitor.visit_seq(SeqDeserializer { iter: a.into_iter() }),
+            _ => Err(de::Error::custom("expected a tuple variant")),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Some(AsonValue::Object(m)) => visitor.visit_map(MapDeserializer { iter: m.into_iter(), value: None }),
+            _ => Err(de::Error::custom("expected a struct variant")),
+        }
+    }
+}
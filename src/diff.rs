@@ -0,0 +1,117 @@
+use core::fmt;
+
+use crate::ast::AsonValue;
+use crate::query::{index_path, member_path};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added { path: String, value: AsonValue },
+    Removed { path: String, value: AsonValue },
+    Changed { path: String, old: AsonValue, new: AsonValue },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added { path, value } => write!(f, "+ {}: {}", path, value.to_json()),
+            Change::Removed { path, value } => write!(f, "- {}: {}", path, value.to_json()),
+            Change::Changed { path, old, new } => write!(f, "~ {}: {} -> {}", path, old.to_json(), new.to_json()),
+        }
+    }
+}
+
+/// Lists the changes that turn `old` into `new`. Object members are compared
+/// by key and arrays are aligned on their common elements, so an insertion
+/// in the middle of an array shows up as one added element (very long arrays
+/// that differ throughout are compared index by index). Paths of removed
+/// elements refer to `old`, every other path refers to `new`.
+pub fn diff(old: &AsonValue, new: &AsonValue) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_values(old, new, "$", &mut changes);
+    changes
+}
+
+fn diff_values(old: &AsonValue, new: &AsonValue, path: &str, out: &mut Vec<Change>) {
+    match (old, new) {
+        (AsonValue::Object(a), AsonValue::Object(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys().filter(|k| !a.contains_key(*k))).collect();
+            keys.sort();
+            for key in keys {
+                let path = member_path(path, key);
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => diff_values(x, y, &path, out),
+                    (Some(x), None) => out.push(Change::Removed { path, value: x.clone() }),
+                    (None, Some(y)) => out.push(Change::Added { path, value: y.clone() }),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        (AsonValue::Array(a), AsonValue::Array(b)) => diff_arrays(a, b, path, out),
+        _ if old == new => {}
+        _ => out.push(Change::Changed { path: path.to_string(), old: old.clone(), new: new.clone() }),
+    }
+}
+
+/// Arrays whose differing middles would need a larger table than this are
+/// compared index by index instead, which keeps memory bounded.
+const MAX_LCS_CELLS: usize = 1 << 22;
+
+fn diff_arrays(a: &[AsonValue], b: &[AsonValue], path: &str, out: &mut Vec<Change>) {
+    // Only the middle, past the common prefix and suffix, needs aligning.
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (x, y) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    if (x.len() + 1).saturating_mul(y.len() + 1) > MAX_LCS_CELLS {
+        removed.extend(prefix..prefix + x.len());
+        added.extend(prefix..prefix + y.len());
+        flush_run(a, b, &mut removed, &mut added, path, out);
+        return;
+    }
+
+    // Longest common subsequence table, lcs[i][j] covers x[i..] and y[j..].
+    let mut lcs = vec![vec![0usize; y.len() + 1]; x.len() + 1];
+    for i in (0..x.len()).rev() {
+        for j in (0..y.len()).rev() {
+            lcs[i][j] = if x[i] == y[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < x.len() || j < y.len() {
+        if i < x.len() && j < y.len() && x[i] == y[j] {
+            flush_run(a, b, &mut removed, &mut added, path, out);
+            i += 1;
+            j += 1;
+        } else if j < y.len() && (i == x.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            added.push(prefix + j);
+            j += 1;
+        } else {
+            removed.push(prefix + i);
+            i += 1;
+        }
+    }
+    flush_run(a, b, &mut removed, &mut added, path, out);
+}
+
+/// Reports a run of unmatched elements. Elements removed and added at the
+/// same spot are paired up and diffed, the rest are plain additions or removals.
+fn flush_run(a: &[AsonValue], b: &[AsonValue], removed: &mut Vec<usize>, added: &mut Vec<usize>, path: &str, out: &mut Vec<Change>) {
+    let paired = removed.len().min(added.len());
+    for k in 0..paired {
+        diff_values(&a[removed[k]], &b[added[k]], &index_path(path, added[k]), out);
+    }
+    for &i in &removed[paired..] {
+        out.push(Change::Removed { path: index_path(path, i), value: a[i].clone() });
+    }
+    for &j in &added[paired..] {
+        out.push(Change::Added { path: index_path(path, j), value: b[j].clone() });
+    }
+    removed.clear();
+    added.clear();
+}
@@ -0,0 +1,50 @@
+use crate::cst::{Document, Node, NodeKind};
+use crate::query::{index_path, member_path};
+
+/// A documented object member.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberDoc {
+    pub path: String,
+    pub doc: String,
+}
+
+/// Collects the `\\\` doc-comments written above object members, in source
+/// order. Members inside expression-s are not documented.
+pub fn member_docs(source: &str) -> Result<Vec<MemberDoc>, String> {
+    let document = Document::parse(source).map_err(|e| e.to_string())?;
+    let mut docs = Vec::new();
+    walk(document.root(), "$", &mut docs);
+    Ok(docs)
+}
+
+fn walk(node: &Node, path: &str, docs: &mut Vec<MemberDoc>) {
+    match &node.kind {
+        NodeKind::Object { members, .. } => {
+            for member in members {
+                // The path under a computed key is only known once evaluated.
+                if member.computed {
+                    continue;
+                }
+                // Members of a spread object land in this one.
+                if member.spread {
+                    walk(&member.value, path, docs);
+                    continue;
+                }
+                let path = member_path(path, &member.key);
+                if let Some(doc) = &member.doc {
+                    docs.push(MemberDoc { path: path.clone(), doc: doc.clone() });
+                }
+                walk(&member.value, &path, docs);
+            }
+        }
+        NodeKind::Array { elements, .. } => {
+            // Indexes are unknown after a spread, until it is evaluated.
+            let known = elements.iter().take_while(|e| !matches!(e.value.kind, NodeKind::Spread(_)));
+            for (i, element) in known.enumerate() {
+                walk(&element.value, &index_path(path, i), docs);
+            }
+        }
+        NodeKind::Spread(value) => walk(value, path, docs),
+        NodeKind::Value | NodeKind::Expr => {}
+    }
+}
@@ -0,0 +1,342 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::ast::{json_string, AsonNumber, AsonValue};
+use crate::query::{index_path, member_path};
+
+/// The names accepted by `emitter`.
+pub const FORMATS: &[&str] = &["json", "canonical-json", "yaml", "toml", "ason"];
+
+#[derive(Debug)]
+pub struct EmitError {
+    msg: String,
+}
+
+impl fmt::Display for EmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+pub type EmitResult<T> = Result<T, EmitError>;
+
+fn error<T>(msg: String) -> EmitResult<T> {
+    Err(EmitError { msg })
+}
+
+/// Writes a value in some output format. Functions are written like `null`.
+pub trait Emitter {
+    fn emit(&self, value: &AsonValue) -> EmitResult<String>;
+}
+
+/// Looks up an emitter by one of the names in `FORMATS`.
+pub fn emitter(format: &str) -> Option<Box<dyn Emitter>> {
+    match format {
+        "json" => Some(Box::new(Json)),
+        "canonical-json" => Some(Box::new(CanonicalJson)),
+        "yaml" => Some(Box::new(Yaml)),
+        "toml" => Some(Box::new(Toml)),
+        "ason" => Some(Box::new(Ason)),
+        _ => None,
+    }
+}
+
+/// JSON indented by two spaces, like `to_pretty_json(2)`.
+pub struct Json;
+
+impl Emitter for Json {
+    fn emit(&self, value: &AsonValue) -> EmitResult<String> {
+        Ok(value.to_pretty_json(2))
+    }
+}
+
+/// ASON source that evaluates back to the value, like `to_ason`.
+pub struct Ason;
+
+impl Emitter for Ason {
+    fn emit(&self, value: &AsonValue) -> EmitResult<String> {
+        Ok(value.to_ason())
+    }
+}
+
+/// RFC 8785 canonical JSON: no whitespace, keys sorted by their UTF-16 code
+/// units and numbers written the way ECMAScript does, so equal values always
+/// give the same bytes.
+pub struct CanonicalJson;
+
+impl Emitter for CanonicalJson {
+    fn emit(&self, value: &AsonValue) -> EmitResult<String> {
+        let mut out = String::new();
+        canonical(value, "$", &mut out)?;
+        Ok(out)
+    }
+}
+
+fn canonical(value: &AsonValue, path: &str, out: &mut String) -> EmitResult<()> {
+    match value {
+        AsonValue::Object(m) => {
+            let mut keys: Vec<&String> = m.keys().collect();
+            keys.sort_by(|a, b| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                canonical_string(key, out);
+                out.push(':');
+                canonical(&m[key], &member_path(path, key), out)?;
+            }
+            out.push('}');
+        }
+        AsonValue::Array(a) => {
+            out.push('[');
+            for (i, v) in a.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                canonical(v, &index_path(path, i), out)?;
+            }
+            out.push(']');
+        }
+        AsonValue::String(s) => canonical_string(s, out),
+        AsonValue::Number(AsonNumber::Integer(i)) => {
+            // Canonical JSON numbers are doubles.
+            if i.unsigned_abs() > 1 << 53 {
+                return error(format!("{} at {} can't be written exactly as canonical JSON", i, path));
+            }
+            out.push_str(&ecmascript_number(*i as f64));
+        }
+        AsonValue::Number(AsonNumber::Float(f)) if !f.is_finite() => {
+            return error(format!("{} at {} can't be written as canonical JSON", f, path));
+        }
+        AsonValue::Number(AsonNumber::Float(f)) => out.push_str(&ecmascript_number(*f)),
+        AsonValue::Boolean(b) => out.push_str(&b.to_string()),
+        AsonValue::Null | AsonValue::Function(_) => out.push_str("null"),
+    }
+    Ok(())
+}
+
+fn canonical_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Formats a finite double like ECMAScript's `Number.prototype.toString`.
+fn ecmascript_number(f: f64) -> String {
+    if f == 0.0 {
+        return "0".into();
+    }
+    // `{:e}` gives the shortest digits that read back as `f`.
+    let scientific = format!("{:e}", f.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap() + 1;
+    let sign = if f < 0.0 { "-" } else { "" };
+    let number = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let exponent = if n > 0 { format!("+{}", n - 1) } else { (n - 1).to_string() };
+        match digits.split_at(1) {
+            (first, "") => format!("{}e{}", first, exponent),
+            (first, rest) => format!("{}.{}e{}", first, rest, exponent),
+        }
+    };
+    format!("{}{}", sign, number)
+}
+
+/// Block-style YAML with sorted keys. Strings are quoted whenever a plain
+/// scalar could read as something else.
+pub struct Yaml;
+
+impl Emitter for Yaml {
+    fn emit(&self, value: &AsonValue) -> EmitResult<String> {
+        Ok(yaml(value, 0))
+    }
+}
+
+fn yaml(value: &AsonValue, indent: usize) -> String {
+    let pad = " ".repeat(indent);
+    match value {
+        AsonValue::Object(m) if !m.is_empty() => sorted(m)
+            .into_iter()
+            .map(|(k, v)| match v {
+                v if is_block(v) => format!("{}{}:\n{}", pad, yaml_string(k), yaml(v, indent + 2)),
+                v => format!("{}{}: {}", pad, yaml_string(k), yaml(v, 0)),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        AsonValue::Array(a) if !a.is_empty() => a
+            .iter()
+            .map(|v| match v {
+                // The item's first line goes right after its dash.
+                v if is_block(v) => format!("{}- {}", pad, &yaml(v, indent + 2)[indent + 2..]),
+                v => format!("{}- {}", pad, yaml(v, 0)),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        AsonValue::Object(_) => format!("{}{{}}", pad),
+        AsonValue::Array(_) => format!("{}[]", pad),
+        AsonValue::String(s) => format!("{}{}", pad, yaml_string(s)),
+        AsonValue::Number(AsonNumber::Float(f)) if f.is_nan() => format!("{}.nan", pad),
+        AsonValue::Number(AsonNumber::Float(f)) if f.is_infinite() => {
+            format!("{}{}.inf", pad, if *f < 0.0 { "-" } else { "" })
+        }
+        AsonValue::Number(AsonNumber::Float(f)) => format!("{}{}", pad, float_literal(*f)),
+        AsonValue::Number(n) => format!("{}{}", pad, n),
+        AsonValue::Boolean(b) => format!("{}{}", pad, b),
+        AsonValue::Null | AsonValue::Function(_) => format!("{}null", pad),
+    }
+}
+
+fn is_block(value: &AsonValue) -> bool {
+    match value {
+        AsonValue::Object(m) => !m.is_empty(),
+        AsonValue::Array(a) => !a.is_empty(),
+        _ => false,
+    }
+}
+
+fn yaml_string(s: &str) -> String {
+    const RESERVED: &[&str] = &["null", "~", "true", "false", "yes", "no", "on", "off", "y", "n"];
+    let plain = s.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | ' '))
+        && !s.ends_with(' ')
+        && !RESERVED.contains(&s.to_lowercase().as_str())
+        && !matches!(s.to_lowercase().as_str(), ".inf" | ".nan");
+    if plain { s.to_string() } else { json_string(s) }
+}
+
+/// TOML with sorted keys. The value must be an object, and `null` (which
+/// TOML has no way to write) is an error.
+pub struct Toml;
+
+impl Emitter for Toml {
+    fn emit(&self, value: &AsonValue) -> EmitResult<String> {
+        let AsonValue::Object(m) = value else {
+            return error(format!("TOML documents must be objects, got {}", value.type_name()));
+        };
+        let mut out = String::new();
+        toml_table(m, &[], "$", &mut out)?;
+        Ok(out.trim_end().to_string())
+    }
+}
+
+fn toml_table(m: &HashMap<String, AsonValue>, keys: &[String], path: &str, out: &mut String) -> EmitResult<()> {
+    let members = sorted(m);
+    // Plain values come first, they would otherwise belong to the last table.
+    for (k, v) in &members {
+        if !v.is_object() && !is_table_array(v) {
+            let value = toml_inline(v, &member_path(path, k))?;
+            out.push_str(&format!("{} = {}\n", toml_key(k), value));
+        }
+    }
+    for (k, v) in &members {
+        let path = member_path(path, k);
+        let keys = [keys, &[k.to_string()]].concat();
+        let header: Vec<String> = keys.iter().map(|k| toml_key(k)).collect();
+        match v {
+            AsonValue::Object(m) => {
+                out.push_str(&format!("\n[{}]\n", header.join(".")));
+                toml_table(m, &keys, &path, out)?;
+            }
+            AsonValue::Array(a) if is_table_array(v) => {
+                for (i, table) in a.iter().enumerate() {
+                    out.push_str(&format!("\n[[{}]]\n", header.join(".")));
+                    toml_table(table.as_object().unwrap(), &keys, &index_path(&path, i), out)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn is_table_array(value: &AsonValue) -> bool {
+    matches!(value, AsonValue::Array(a) if !a.is_empty() && a.iter().all(AsonValue::is_object))
+}
+
+fn toml_inline(value: &AsonValue, path: &str) -> EmitResult<String> {
+    Ok(match value {
+        AsonValue::Object(m) => {
+            let members = sorted(m)
+                .into_iter()
+                .map(|(k, v)| Ok(format!("{} = {}", toml_key(k), toml_inline(v, &member_path(path, k))?)))
+                .collect::<EmitResult<Vec<_>>>()?;
+            if members.is_empty() { "{}".into() } else { format!("{{ {} }}", members.join(", ")) }
+        }
+        AsonValue::Array(a) => {
+            let elements = a
+                .iter()
+                .enumerate()
+                .map(|(i, v)| toml_inline(v, &index_path(path, i)))
+                .collect::<EmitResult<Vec<_>>>()?;
+            format!("[{}]", elements.join(", "))
+        }
+        AsonValue::String(s) => toml_string(s),
+        AsonValue::Number(AsonNumber::Float(f)) if f.is_nan() => "nan".into(),
+        AsonValue::Number(AsonNumber::Float(f)) if f.is_infinite() => if *f < 0.0 { "-inf" } else { "inf" }.into(),
+        AsonValue::Number(AsonNumber::Float(f)) => float_literal(*f),
+        AsonValue::Number(n) => n.to_string(),
+        AsonValue::Boolean(b) => b.to_string(),
+        AsonValue::Null | AsonValue::Function(_) => {
+            return error(format!("{} at {} can't be written as TOML", value.type_name(), path));
+        }
+    })
+}
+
+fn toml_key(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        key.to_string()
+    } else {
+        toml_string(key)
+    }
+}
+
+fn toml_string(s: &str) -> String {
+    let mut result = "\"".to_string();
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\u{8}' => result.push_str("\\b"),
+            '\t' => result.push_str("\\t"),
+            '\n' => result.push_str("\\n"),
+            '\u{c}' => result.push_str("\\f"),
+            '\r' => result.push_str("\\r"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04X}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// A finite float that can't be mistaken for an integer.
+fn float_literal(f: f64) -> String {
+    let literal = f.to_string();
+    if literal.contains(['.', 'e']) { literal } else { format!("{}.0", literal) }
+}
+
+fn sorted(m: &HashMap<String, AsonValue>) -> Vec<(&String, &AsonValue)> {
+    let mut members: Vec<_> = m.iter().collect();
+    members.sort_by_key(|(k, _)| *k);
+    members
+}
@@ -1,25 +1,72 @@
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::Read;
+use std::io;
+use std::ops;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use crate::ast::AsonValue;
 use crate::ast::AsonNumber;
 
+use crate::io::{AsonIo, SystemIo};
+use crate::parser::{DuplicateKeys, ParserError};
+use crate::limits::LimitKind;
+use crate::limits::Limits;
 use crate::runtime;
 use crate::runtime::AsonExpectedArgs;
 use crate::runtime::AsonFunction;
 use crate::runtime::RuntimeError;
 
+/// The result of evaluating an included file, kept so a file shared by
+/// several includes is only evaluated once.
+#[derive(Debug, Clone)]
+pub struct Included {
+    pub value: AsonValue,
+    pub bindings: HashMap<String, AsonValue>,
+}
+
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct Environment {
+    /// Change bindings through `add_constant` and `define_function`, so
+    /// running programs see the change.
     pub symbols: HashMap<String, AsonValue>,
+    pub limits: Limits,
+    pub io: Box<dyn AsonIo>,
+    pub duplicate_keys: DuplicateKeys,
+    /// Warnings reported while parsing, such as duplicate keys.
+    pub warnings: Vec<ParserError>,
+    steps: u64,
+    depth: usize,
+    generation: u64,
+    started: Instant,
+    files: Vec<PathBuf>,
+    includes: HashMap<PathBuf, Included>,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Environment {
     pub fn new() -> Self {
+        Self::with_limits(Limits::default())
+    }
+
+    pub fn with_limits(limits: Limits) -> Self {
         let mut result = Self {
             symbols: HashMap::new(),
+            limits,
+            io: Box::new(SystemIo),
+            duplicate_keys: DuplicateKeys::default(),
+            warnings: Vec::new(),
+            steps: 0,
+            depth: 0,
+            generation: 0,
+            started: Instant::now(),
+            files: Vec::new(),
+            includes: HashMap::new(),
         };
 
         // Arithmatics
@@ -30,20 +77,45 @@ impl Environment {
 
         // IO function
         result.define_function("write-line".into(), _write_line, AsonExpectedArgs::AtLeast(1));
+        result.define_function("write-error".into(), _write_error, AsonExpectedArgs::AtLeast(1));
         result.define_function("read-file-to-string".into(), _read_file_to_string, AsonExpectedArgs::Exact(1));
 
+        // Strings
+        result.define_function("concat".into(), _concat, AsonExpectedArgs::AtLeast(1));
+
+        // Collections
+        result.define_function("map".into(), _map, AsonExpectedArgs::AtLeast(2));
+        result.define_function("filter".into(), _filter, AsonExpectedArgs::AtLeast(2));
+        result.define_function("reduce".into(), _reduce, AsonExpectedArgs::AtLeast(3));
+        result.define_function("any?".into(), _any, AsonExpectedArgs::AtLeast(2));
+        result.define_function("all?".into(), _all, AsonExpectedArgs::AtLeast(2));
+        result.define_function("sort-by".into(), _sort_by, AsonExpectedArgs::AtLeast(2));
+        result.define_function("group-by".into(), _group_by, AsonExpectedArgs::AtLeast(2));
+        result.define_function("zip".into(), _zip, AsonExpectedArgs::AtLeast(1));
+        result.define_function("flat-map".into(), _flat_map, AsonExpectedArgs::AtLeast(2));
+        result.define_function("map-values".into(), _map_values, AsonExpectedArgs::AtLeast(2));
+
         // Variables
         result.define_function("defvar".into(), _define_var, AsonExpectedArgs::Exact(2));
 
+        // Process environment
+        result.define_function("env".into(), _env, AsonExpectedArgs::Between(1, 2));
+
+        // Other documents
+        result.define_function("include".into(), _include, AsonExpectedArgs::Exact(1));
+        result.define_function("import".into(), _import, AsonExpectedArgs::Exact(1));
+
         result
     }
 
     #[allow(dead_code)]
     pub fn add_constant(&mut self, name: String, value: AsonValue) {
+        self.generation += 1;
         self.symbols.insert(name, value);
     }
 
     pub fn define_function(&mut self, name: String, callback: runtime::Callback, expected_args: AsonExpectedArgs) {
+        self.generation += 1;
         self.symbols.insert(
             name,
             AsonValue::Function(AsonFunction::new(callback, expected_args))
@@ -51,109 +123,426 @@ impl Environment {
     }
 
     pub fn call_fn(&mut self, name: &str, args: Vec<AsonValue>) -> Result<AsonValue, RuntimeError> {
-        let symbol = self.symbols
-                         .get(name)
-                         .unwrap()
-            .clone();
+        let symbol = match self.symbols.get(name) {
+            Some(v) => v.clone(),
+            None => return Err(RuntimeError::UndefinedSymbol),
+        };
         match symbol {
             AsonValue::Function(f) => f.call(args.as_slice(), self),
             _ => Err(RuntimeError::NotAFunction),
         }
     }
-}
 
-fn _add(args: &[AsonValue], _env: &mut Environment) -> AsonValue {
-    let mut result = AsonNumber::Integer(0);
-    for v in args {
-        if let AsonValue::Number(v) = v {
-            result = result + v.clone();
-        } else {
-            panic!("Cannot perform an arithmatic operation on a non number value.");
+    /// Forgets every symbol defined since creation and every cached include,
+    /// keeping the limits and IO.
+    pub fn reset(&mut self) {
+        self.generation += 1;
+        self.symbols = Environment::with_limits(self.limits.clone()).symbols;
+        self.files.clear();
+        self.includes.clear();
+        self.reset_budget();
+    }
+
+    /// Changes whenever a binding does, so a resolved symbol can be kept
+    /// until then.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Restarts the step counter, nesting depth and clock for a new document.
+    pub fn reset_budget(&mut self) {
+        self.steps = 0;
+        self.depth = 0;
+        self.started = Instant::now();
+    }
+
+    /// Accounts for one evaluation step, failing once the step budget or the
+    /// wall-clock timeout is used up.
+    pub fn step(&mut self) -> Result<(), RuntimeError> {
+        self.steps += 1;
+        if self.steps > self.limits.max_steps {
+            return Err(RuntimeError::LimitExceeded { kind: LimitKind::Steps, max: self.limits.max_steps });
         }
+        if let Some(timeout) = self.limits.timeout
+            && self.started.elapsed() > timeout
+        {
+            return Err(RuntimeError::LimitExceeded { kind: LimitKind::Timeout, max: timeout.as_millis() as u64 });
+        }
+        Ok(())
     }
-    AsonValue::Number(result)
-}
 
-fn _sub(args: &[AsonValue], _env: &mut Environment) -> AsonValue {
-    let mut result = AsonNumber::Integer(0);
-    for v in args {
-        if let AsonValue::Number(v) = v {
-            result = result - v.clone();
-        } else {
-            panic!("Cannot perform an arithmatic operation on a non number value.");
+    pub fn enter(&mut self) -> Result<(), RuntimeError> {
+        if self.depth >= self.limits.max_depth {
+            return Err(RuntimeError::LimitExceeded { kind: LimitKind::Depth, max: self.limits.max_depth as u64 });
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub fn leave(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    pub fn push_file(&mut self, path: &Path) {
+        let path = self.io.canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.files.push(path);
+    }
+
+    pub fn pop_file(&mut self) {
+        self.files.pop();
+    }
+
+    /// Resolves `path` against the directory of the file being evaluated.
+    pub fn resolve_path(&self, path: &str) -> PathBuf {
+        let path = Path::new(path);
+        let resolved = match self.files.last().and_then(|f| f.parent()) {
+            Some(dir) if path.is_relative() => dir.join(path),
+            _ => path.to_path_buf(),
+        };
+        self.io.canonicalize(&resolved).unwrap_or(resolved)
+    }
+
+    pub fn read_to_string(&mut self, path: &Path) -> Result<String, RuntimeError> {
+        let max = self.limits.max_file_read_bytes;
+        self.io.read_to_string(path, max).map_err(|e| match e.kind() {
+            io::ErrorKind::FileTooLarge => RuntimeError::LimitExceeded { kind: LimitKind::FileReadBytes, max },
+            _ => RuntimeError::Io(format!("{}: {}", path.display(), e)),
+        })
+    }
+
+    /// Evaluates another document with only the builtins in scope, so its
+    /// value doesn't depend on who includes it and can be cached, and returns
+    /// that value together with the symbols it defined.
+    pub fn load_file(&mut self, path: &str) -> Result<Included, RuntimeError> {
+        let path = self.resolve_path(path);
+        if self.files.contains(&path) {
+            return Err(RuntimeError::IncludeCycle(path.display().to_string()));
         }
+        if let Some(included) = self.includes.get(&path) {
+            return Ok(included.clone());
+        }
+
+        let source = self.read_to_string(&path)?;
+        let builtins = Environment::with_limits(self.limits.clone()).symbols;
+        let saved = std::mem::replace(&mut self.symbols, builtins.clone());
+        self.generation += 1;
+        self.files.push(path.clone());
+        let result = AsonValue::evaluate(&source, &path.display().to_string(), self);
+        self.files.pop();
+        let bindings = std::mem::replace(&mut self.symbols, saved)
+            .into_iter()
+            .filter(|(k, v)| builtins.get(k) != Some(v))
+            .collect();
+        self.generation += 1;
+
+        let included = Included {
+            value: result.map_err(|e| RuntimeError::IncludeFailed(e.to_string()))?,
+            bindings,
+        };
+        self.includes.insert(path, included.clone());
+        Ok(included)
     }
-    AsonValue::Number(result)
+
+    pub fn check_size(&self, value: &AsonValue) -> Result<(), RuntimeError> {
+        match value {
+            AsonValue::String(s) if s.len() > self.limits.max_string_size => {
+                Err(RuntimeError::LimitExceeded { kind: LimitKind::StringSize, max: self.limits.max_string_size as u64 })
+            }
+            AsonValue::Array(a) if a.len() > self.limits.max_array_size => {
+                Err(RuntimeError::LimitExceeded { kind: LimitKind::ArraySize, max: self.limits.max_array_size as u64 })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn number_arg<'a>(name: &str, value: &'a AsonValue) -> Result<&'a AsonNumber, RuntimeError> {
+    value.as_number().ok_or_else(|| RuntimeError::InvalidArgument(format!("{} expects numbers, got {}.", name, value.type_name())))
+}
+
+fn string_arg<'a>(name: &str, value: &'a AsonValue) -> Result<&'a str, RuntimeError> {
+    value.as_str().ok_or_else(|| RuntimeError::InvalidArgument(format!("{} expects a string, got {}.", name, value.type_name())))
 }
 
-fn _mul(args: &[AsonValue], _env: &mut Environment) -> AsonValue {
-    let mut result = AsonNumber::Integer(1);
+/// Folds `args` into `result` with `op`, using `checked` when both sides are
+/// integers so an overflow is an error rather than a panic.
+fn arithmetic(
+    name: &str,
+    args: &[AsonValue],
+    mut result: AsonNumber,
+    checked: fn(i64, i64) -> Option<i64>,
+    op: fn(AsonNumber, AsonNumber) -> AsonNumber,
+) -> Result<AsonValue, RuntimeError> {
     for v in args {
-        if let AsonValue::Number(v) = v {
-            result = result * v.clone();
-        } else {
-            panic!("Cannot perform an arithmatic operation on a non number value.");
-        }
+        result = match (result, number_arg(name, v)?) {
+            (AsonNumber::Integer(a), AsonNumber::Integer(b)) => match checked(a, *b) {
+                Some(i) => AsonNumber::Integer(i),
+                None => return Err(RuntimeError::InvalidArgument(format!("Integer overflow in {}.", name))),
+            },
+            (a, b) => op(a, b.clone()),
+        };
     }
-    AsonValue::Number(result)
+    Ok(AsonValue::Number(result))
+}
+
+fn _add(args: &[AsonValue], _env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    arithmetic("+", args, AsonNumber::Integer(0), i64::checked_add, ops::Add::add)
+}
+
+fn _sub(args: &[AsonValue], _env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    arithmetic("-", args, AsonNumber::Integer(0), i64::checked_sub, ops::Sub::sub)
+}
+
+fn _mul(args: &[AsonValue], _env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    arithmetic("*", args, AsonNumber::Integer(1), i64::checked_mul, ops::Mul::mul)
 }
 
-fn _div(args: &[AsonValue], _env: &mut Environment) -> AsonValue {
+fn _div(args: &[AsonValue], _env: &mut Environment) -> Result<AsonValue, RuntimeError> {
     assert!(args.len() >= 2);
 
-    let first_value = &args[0];
-    let mut result = match first_value.as_number() {
-        Some(v) => v.clone(),
-        None => panic!("Cannot perform an arithmatic operation on a non number value."),
-    };
+    let mut result = number_arg("/", &args[0])?.clone();
     for v in &args[1..] {
-        if let AsonValue::Number(v) = v {
-            result = result / v.clone();
-        } else {
-            panic!("Cannot perform an arithmatic operation on a non number value.");
-        }
+        result = result / number_arg("/", v)?.clone();
     }
-    AsonValue::Number(result)
+    Ok(AsonValue::Number(result))
 }
 
-fn _write_line(args: &[AsonValue], _env: &mut Environment) -> AsonValue {
-    assert!(args.len() > 0);
+fn _write_line(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    assert!(!args.is_empty());
+
+    env.io.write_stdout(&format_line(args)).map_err(|e| RuntimeError::Io(e.to_string()))?;
+    Ok(AsonValue::Null)
+}
+
+fn _write_error(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    assert!(!args.is_empty());
+
+    env.io.write_stderr(&format_line(args)).map_err(|e| RuntimeError::Io(e.to_string()))?;
+    Ok(AsonValue::Null)
+}
+
+/// Joins its arguments into one string; anything but a string is written as JSON.
+fn _concat(args: &[AsonValue], _env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    let mut result = String::new();
+    for v in args {
+        match v {
+            AsonValue::String(s) => result.push_str(s),
+            v => result.push_str(&v.to_json()),
+        }
+    }
+    Ok(AsonValue::String(result))
+}
 
+fn format_line(args: &[AsonValue]) -> String {
+    let mut line = String::new();
     for v in args {
-        print!("{} ", v);
+        line.push_str(&format!("{} ", v));
     }
-    println!();
-    AsonValue::Null
+    line.push('\n');
+    line
 }
 
-fn _read_file_to_string(args: &[AsonValue], _env: &mut Environment) -> AsonValue {
+fn _read_file_to_string(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
     assert!(args.len() == 1);
 
-    if let AsonValue::String(ref s) = args[0] {
-        let mut result = String::new();
-        let mut file = match File::open(s) {
-            Ok(v) => v,
-            Err(e) => todo!("{:?}", e),
-        };
-        if let Err(e) = file.read_to_string(&mut result) {
-            todo!("{:?}", e);
-        }
-        AsonValue::String(result)
-    } else {
-        panic!("read-to-string expects a string argument.");
-    }
+    let path = string_arg("read-file-to-string", &args[0])?;
+    Ok(AsonValue::String(env.read_to_string(Path::new(path))?))
 }
 
-fn _define_var(args: &[AsonValue], env: &mut Environment) -> AsonValue {
+fn _define_var(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
     assert!(args.len() == 2);
 
-    let id = match &args[1] {
-        AsonValue::String(id) => id.clone(),
-        _ => panic!("Mispatch type"),
-    };
+    let id = string_arg("defvar", &args[1])?.to_string();
     let value = args[0].clone();
 
     env.add_constant(id, value.clone());
 
-    value
+    Ok(value)
+}
+
+fn _include(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    assert!(args.len() == 1);
+
+    let path = string_arg("include", &args[0])?;
+    Ok(env.load_file(path)?.value)
+}
+
+fn _import(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    assert!(args.len() == 1);
+
+    let path = string_arg("import", &args[0])?;
+    for (name, value) in env.load_file(path)?.bindings {
+        env.add_constant(name, value);
+    }
+    Ok(AsonValue::Null)
+}
+
+fn _env(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    assert!(!args.is_empty() && args.len() <= 2);
+
+    let name = string_arg("env", &args[0])?;
+    match env.io.var(name) {
+        Some(v) => Ok(AsonValue::String(v)),
+        None if args.len() == 2 => Ok(args[1].clone()),
+        None => Err(RuntimeError::EnvVarNotSet(name.into())),
+    }
+}
+
+// The collection builtins take `)collection function extra...` and call
+// `)element extra... function(` for each element, so a builtin can be
+// given more arguments: `)names concat "-svc" map(` suffixes every name.
+
+fn array_arg<'a>(name: &str, value: &'a AsonValue) -> Result<&'a Vec<AsonValue>, RuntimeError> {
+    value.as_array().ok_or_else(|| RuntimeError::InvalidArgument(format!("{} expects an array, got {}.", name, value.type_name())))
+}
+
+fn function_arg<'a>(name: &str, value: &'a AsonValue) -> Result<&'a AsonFunction, RuntimeError> {
+    match value {
+        AsonValue::Function(f) => Ok(f),
+        v => Err(RuntimeError::InvalidArgument(format!("{} expects a function, got {}.", name, v.type_name()))),
+    }
+}
+
+/// Calls `function` with `first` followed by `extra`.
+fn apply(function: &AsonFunction, first: &[&AsonValue], extra: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    env.step()?;
+    let mut args: Vec<AsonValue> = first.iter().map(|v| (*v).clone()).collect();
+    args.extend_from_slice(extra);
+    function.call(&args, env)
+}
+
+/// `false` and `null` are false, anything else is true.
+fn truthy(value: &AsonValue) -> bool {
+    !matches!(value, AsonValue::Boolean(false) | AsonValue::Null)
+}
+
+fn _map(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    let (elements, function) = (array_arg("map", &args[0])?, function_arg("map", &args[1])?);
+    let mut result = Vec::with_capacity(elements.len());
+    for element in elements {
+        result.push(apply(function, &[element], &args[2..], env)?);
+    }
+    Ok(AsonValue::Array(result))
+}
+
+fn _filter(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    let (elements, function) = (array_arg("filter", &args[0])?, function_arg("filter", &args[1])?);
+    let mut result = Vec::new();
+    for element in elements {
+        if truthy(&apply(function, &[element], &args[2..], env)?) {
+            result.push(element.clone());
+        }
+    }
+    Ok(AsonValue::Array(result))
+}
+
+/// `)array function initial extra... reduce(` calls `)accumulator element extra... function(`.
+fn _reduce(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    let (elements, function) = (array_arg("reduce", &args[0])?, function_arg("reduce", &args[1])?);
+    let mut accumulator = args[2].clone();
+    for element in elements {
+        accumulator = apply(function, &[&accumulator, element], &args[3..], env)?;
+    }
+    Ok(accumulator)
+}
+
+fn _any(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    let (elements, function) = (array_arg("any?", &args[0])?, function_arg("any?", &args[1])?);
+    for element in elements {
+        if truthy(&apply(function, &[element], &args[2..], env)?) {
+            return Ok(AsonValue::Boolean(true));
+        }
+    }
+    Ok(AsonValue::Boolean(false))
+}
+
+fn _all(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    let (elements, function) = (array_arg("all?", &args[0])?, function_arg("all?", &args[1])?);
+    for element in elements {
+        if !truthy(&apply(function, &[element], &args[2..], env)?) {
+            return Ok(AsonValue::Boolean(false));
+        }
+    }
+    Ok(AsonValue::Boolean(true))
+}
+
+/// Sorts by the key the function gives each element, keeping the order of
+/// equal keys. Keys must be all numbers or all strings.
+fn _sort_by(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    let (elements, function) = (array_arg("sort-by", &args[0])?, function_arg("sort-by", &args[1])?);
+    let mut keyed = Vec::with_capacity(elements.len());
+    for element in elements {
+        keyed.push((apply(function, &[element], &args[2..], env)?, element.clone()));
+    }
+    let mut error = None;
+    keyed.sort_by(|(a, _), (b, _)| {
+        let order = match (a, b) {
+            (AsonValue::Number(a), AsonValue::Number(b)) => number_f64(a).partial_cmp(&number_f64(b)),
+            (AsonValue::String(a), AsonValue::String(b)) => Some(a.cmp(b)),
+            _ => None,
+        };
+        order.unwrap_or_else(|| {
+            error.get_or_insert_with(|| format!("sort-by can't compare {} with {}.", a.type_name(), b.type_name()));
+            std::cmp::Ordering::Equal
+        })
+    });
+    match error {
+        Some(msg) => Err(RuntimeError::InvalidArgument(msg)),
+        None => Ok(AsonValue::Array(keyed.into_iter().map(|(_, v)| v).collect())),
+    }
+}
+
+fn number_f64(n: &AsonNumber) -> f64 {
+    match *n {
+        AsonNumber::Integer(i) => i as f64,
+        AsonNumber::Float(f) => f,
+    }
+}
+
+/// Groups elements by the key the function gives them, written like `concat` does.
+fn _group_by(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    let (elements, function) = (array_arg("group-by", &args[0])?, function_arg("group-by", &args[1])?);
+    let mut groups: HashMap<String, AsonValue> = HashMap::new();
+    for element in elements {
+        let key = match apply(function, &[element], &args[2..], env)? {
+            AsonValue::String(s) => s,
+            v => v.to_json(),
+        };
+        if let AsonValue::Array(group) = groups.entry(key).or_insert_with(|| AsonValue::Array(Vec::new())) {
+            group.push(element.clone());
+        }
+    }
+    Ok(AsonValue::Object(groups))
+}
+
+/// Pairs up the elements of each array, as long as the shortest one.
+fn _zip(args: &[AsonValue], _env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    let arrays = args.iter().map(|a| array_arg("zip", a)).collect::<Result<Vec<_>, _>>()?;
+    let len = arrays.iter().map(|a| a.len()).min().unwrap_or(0);
+    let zipped = (0..len).map(|i| AsonValue::Array(arrays.iter().map(|a| a[i].clone()).collect())).collect();
+    Ok(AsonValue::Array(zipped))
+}
+
+fn _flat_map(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    let (elements, function) = (array_arg("flat-map", &args[0])?, function_arg("flat-map", &args[1])?);
+    let mut result = Vec::new();
+    for element in elements {
+        match apply(function, &[element], &args[2..], env)? {
+            AsonValue::Array(mapped) => result.extend(mapped),
+            v => return Err(RuntimeError::InvalidArgument(format!("flat-map expects the function to return arrays, got {}.", v.type_name()))),
+        }
+    }
+    Ok(AsonValue::Array(result))
+}
+
+fn _map_values(args: &[AsonValue], env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    let AsonValue::Object(members) = &args[0] else {
+        return Err(RuntimeError::InvalidArgument(format!("map-values expects an object, got {}.", args[0].type_name())));
+    };
+    let function = function_arg("map-values", &args[1])?;
+    let mut result = HashMap::with_capacity(members.len());
+    for (key, value) in members {
+        result.insert(key.clone(), apply(function, &[value], &args[2..], env)?);
+    }
+    Ok(AsonValue::Object(result))
 }
@@ -0,0 +1,143 @@
+use core::fmt;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+/// Everything the builtins need from the outside world. `Environment` uses
+/// `SystemIo` unless a host swaps in its own implementation.
+pub trait AsonIo: fmt::Debug {
+    fn write_stdout(&mut self, s: &str) -> io::Result<()>;
+    fn write_stderr(&mut self, s: &str) -> io::Result<()>;
+
+    /// Reads a whole file, failing with `ErrorKind::FileTooLarge` when it is
+    /// longer than `max` bytes instead of reading the rest of it.
+    fn read_to_string(&mut self, path: &Path, max: u64) -> io::Result<String>;
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// The value of an environment variable, if it is set.
+    fn var(&self, name: &str) -> Option<String>;
+}
+
+#[derive(Debug, Default)]
+pub struct SystemIo;
+
+impl AsonIo for SystemIo {
+    fn write_stdout(&mut self, s: &str) -> io::Result<()> {
+        io::stdout().write_all(s.as_bytes())
+    }
+
+    fn write_stderr(&mut self, s: &str) -> io::Result<()> {
+        io::stderr().write_all(s.as_bytes())
+    }
+
+    fn read_to_string(&mut self, path: &Path, max: u64) -> io::Result<String> {
+        let mut bytes = Vec::new();
+        File::open(path)?.take(max + 1).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 > max {
+            return Err(io::Error::from(io::ErrorKind::FileTooLarge));
+        }
+        String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::canonicalize(path)
+    }
+
+    fn var(&self, name: &str) -> Option<String> {
+        env::var(name).ok()
+    }
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    files: HashMap<PathBuf, String>,
+    vars: HashMap<String, String>,
+    stdout: String,
+    stderr: String,
+}
+
+/// An in-memory filesystem, environment and output capture. Clones share the same state,
+/// so a host can keep one handle and give another to the `Environment`.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryIo {
+    state: Rc<RefCell<MemoryState>>,
+}
+
+#[allow(dead_code)]
+impl MemoryIo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_file(&self, path: impl AsRef<Path>, content: impl Into<String>) {
+        let path = normalize(path.as_ref());
+        self.state.borrow_mut().files.insert(path, content.into());
+    }
+
+    pub fn set_var(&self, name: impl Into<String>, value: impl Into<String>) {
+        self.state.borrow_mut().vars.insert(name.into(), value.into());
+    }
+
+    pub fn stdout(&self) -> String {
+        self.state.borrow().stdout.clone()
+    }
+
+    pub fn stderr(&self) -> String {
+        self.state.borrow().stderr.clone()
+    }
+}
+
+impl AsonIo for MemoryIo {
+    fn write_stdout(&mut self, s: &str) -> io::Result<()> {
+        self.state.borrow_mut().stdout.push_str(s);
+        Ok(())
+    }
+
+    fn write_stderr(&mut self, s: &str) -> io::Result<()> {
+        self.state.borrow_mut().stderr.push_str(s);
+        Ok(())
+    }
+
+    fn read_to_string(&mut self, path: &Path, max: u64) -> io::Result<String> {
+        match self.state.borrow().files.get(&normalize(path)) {
+            Some(content) if content.len() as u64 > max => Err(io::Error::from(io::ErrorKind::FileTooLarge)),
+            Some(content) => Ok(content.clone()),
+            None => Err(io::Error::from(io::ErrorKind::NotFound)),
+        }
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let path = normalize(path);
+        if self.state.borrow().files.contains_key(&path) {
+            Ok(path)
+        } else {
+            Err(io::Error::from(io::ErrorKind::NotFound))
+        }
+    }
+
+    fn var(&self, name: &str) -> Option<String> {
+        self.state.borrow().vars.get(name).cloned()
+    }
+}
+
+/// Resolves `.` and `..` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                result.pop();
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
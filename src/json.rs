@@ -0,0 +1,261 @@
+use core::fmt;
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::ast::{AsonNumber, AsonValue};
+use crate::limits::Limits;
+
+#[derive(Debug)]
+pub struct JsonError {
+    msg: String,
+    line: usize,
+    column: usize,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.msg)
+    }
+}
+
+pub type JsonResult<T> = Result<T, JsonError>;
+
+/// A small reader for plain JSON, used for inputs that come from outside of
+/// ASON documents (variable files, schemas, patches).
+pub struct JsonReader<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<'a> JsonReader<'a> {
+    pub fn new(input: &'a str) -> Self {
+        JsonReader {
+            chars: input.chars().peekable(),
+            line: 1,
+            column: 1,
+            depth: 0,
+            max_depth: Limits::default().max_depth,
+        }
+    }
+
+    pub fn read(&mut self) -> JsonResult<AsonValue> {
+        let value = self.read_value()?;
+        self.skip_whitespace();
+        match self.chars.peek().copied() {
+            None => Ok(value),
+            Some(c) => Err(self.error(format!("Unexpected character after value: '{}'.", c))),
+        }
+    }
+
+    fn read_value(&mut self) -> JsonResult<AsonValue> {
+        self.skip_whitespace();
+        match self.chars.peek().copied() {
+            Some('{') => self.read_object(),
+            Some('[') => self.read_array(),
+            Some('"') => Ok(AsonValue::String(self.read_string()?)),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.read_number(),
+            Some(c) if c.is_ascii_alphabetic() => self.read_keyword(),
+            Some(c) => Err(self.error(format!("Unexpected character: '{}'.", c))),
+            None => Err(self.error("Unexpected end of input.".into())),
+        }
+    }
+
+    fn enter(&mut self) -> JsonResult<()> {
+        self.depth += 1;
+        if self.depth > self.max_depth {
+            return Err(self.error(format!("Nesting deeper than {} levels.", self.max_depth)));
+        }
+        Ok(())
+    }
+
+    fn read_object(&mut self) -> JsonResult<AsonValue> {
+        self.advance();
+        self.enter()?;
+        let mut members = HashMap::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.advance();
+            self.depth -= 1;
+            return Ok(AsonValue::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.chars.peek() != Some(&'"') {
+                return Err(self.error("Expected a string key.".into()));
+            }
+            let key = self.read_string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            members.insert(key, self.read_value()?);
+            self.skip_whitespace();
+            match self.advance() {
+                Some(',') => continue,
+                Some('}') => break,
+                _ => return Err(self.error("Expected ',' or '}'.".into())),
+            }
+        }
+        self.depth -= 1;
+        Ok(AsonValue::Object(members))
+    }
+
+    fn read_array(&mut self) -> JsonResult<AsonValue> {
+        self.advance();
+        self.enter()?;
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&']') {
+            self.advance();
+            self.depth -= 1;
+            return Ok(AsonValue::Array(elements));
+        }
+        loop {
+            elements.push(self.read_value()?);
+            self.skip_whitespace();
+            match self.advance() {
+                Some(',') => continue,
+                Some(']') => break,
+                _ => return Err(self.error("Expected ',' or ']'.".into())),
+            }
+        }
+        self.depth -= 1;
+        Ok(AsonValue::Array(elements))
+    }
+
+    fn read_string(&mut self) -> JsonResult<String> {
+        self.advance();
+        let mut content = String::new();
+        loop {
+            match self.advance() {
+                None => return Err(self.error("Unterminated string.".into())),
+                Some('"') => break,
+                Some('\\') => match self.advance() {
+                    Some('n') => content.push('\n'),
+                    Some('r') => content.push('\r'),
+                    Some('t') => content.push('\t'),
+                    Some('b') => content.push('\u{8}'),
+                    Some('f') => content.push('\u{c}'),
+                    Some('/') => content.push('/'),
+                    Some('\\') => content.push('\\'),
+                    Some('"') => content.push('"'),
+                    Some('u') => content.push(self.read_unicode_escape()?),
+                    _ => return Err(self.error("Invalid escape sequence.".into())),
+                },
+                Some(c) => content.push(c),
+            }
+        }
+        Ok(content)
+    }
+
+    fn read_unicode_escape(&mut self) -> JsonResult<char> {
+        let high = self.read_hex4()?;
+        if (0xD800..0xDC00).contains(&high) {
+            if self.advance() != Some('\\') || self.advance() != Some('u') {
+                return Err(self.error("Unpaired surrogate in unicode escape.".into()));
+            }
+            let low = self.read_hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("Unpaired surrogate in unicode escape.".into()));
+            }
+            let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+            return char::from_u32(code).ok_or_else(|| self.error("Invalid unicode escape.".into()));
+        }
+        char::from_u32(high).ok_or_else(|| self.error("Invalid unicode escape.".into()))
+    }
+
+    fn read_hex4(&mut self) -> JsonResult<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.advance().and_then(|c| c.to_digit(16)) {
+                Some(d) => code = code * 16 + d,
+                None => return Err(self.error("Invalid unicode escape.".into())),
+            }
+        }
+        Ok(code)
+    }
+
+    fn read_number(&mut self) -> JsonResult<AsonValue> {
+        let mut lexem = String::new();
+        let mut is_float = false;
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                '0'..='9' | '-' | '+' => lexem.push(c),
+                '.' | 'e' | 'E' => {
+                    is_float = true;
+                    lexem.push(c);
+                }
+                _ => break,
+            }
+            self.advance();
+        }
+        if is_float {
+            match lexem.parse::<f64>() {
+                Ok(v) => Ok(AsonValue::Number(AsonNumber::Float(v))),
+                Err(_) => Err(self.error(format!("Invalid number: {}", lexem))),
+            }
+        } else {
+            match lexem.parse::<i64>() {
+                Ok(v) => Ok(AsonValue::Number(AsonNumber::Integer(v))),
+                Err(_) => match lexem.parse::<f64>() {
+                    Ok(v) => Ok(AsonValue::Number(AsonNumber::Float(v))),
+                    Err(_) => Err(self.error(format!("Invalid number: {}", lexem))),
+                },
+            }
+        }
+    }
+
+    fn read_keyword(&mut self) -> JsonResult<AsonValue> {
+        let mut lexem = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if !c.is_ascii_alphabetic() {
+                break;
+            }
+            lexem.push(c);
+            self.advance();
+        }
+        match lexem.as_str() {
+            "true" => Ok(AsonValue::Boolean(true)),
+            "false" => Ok(AsonValue::Boolean(false)),
+            "null" => Ok(AsonValue::Null),
+            _ => Err(self.error(format!("Unexpected word: {}", lexem))),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> JsonResult<()> {
+        match self.advance() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(self.error(format!("Expected '{}'.", expected))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.advance();
+        }
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, msg: String) -> JsonError {
+        JsonError {
+            msg,
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
@@ -1,34 +1,58 @@
 use crate::token::Token;
 use crate::token::TokenKind;
 use crate::token::TokenList;
+use crate::token::{Trivia, TriviaKind};
+use crate::span::Span;
 
 pub struct Lexer<'a> {
     input: &'a str,
     chars: std::str::Chars<'a>,
-    tokens: &'a mut TokenList<'a>,
+    tokens: TokenList<'a>,
     current_char: Option<char>,
     line: usize,
     column: usize,
     index: usize,
+    start: (usize, usize, usize), // index, line and column where the current token begins
+    trivia: Vec<Trivia<'a>>, // comments waiting for the next token
 }
 
 impl<'a> Lexer<'a> {
-    pub fn new(input: &'a str, tokens: &'a mut TokenList<'a>) -> Self {
+    pub fn new(input: &'a str) -> Self {
         let mut chars = input.chars();
         let current_char = chars.next();
         Lexer {
             input,
             chars,
-            tokens,
+            tokens: TokenList::new(),
             current_char,
             line: 1,
             column: 1,
             index: 0,
+            start: (0, 1, 1),
+            trivia: Vec::new(),
         }
     }
 
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The byte offset the lexer stopped at, which is where a scan error happened.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The tokens scanned so far, which is all of them once `scan` succeeded.
+    pub fn into_tokens(self) -> TokenList<'a> {
+        self.tokens
+    }
+
     fn is_digit(ch: char) -> bool {
-        ch >= '0' && ch <= '9'
+        ch.is_ascii_digit()
     }
 
     fn is_symbol(ch: char) -> bool {
@@ -58,48 +82,83 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    fn begin(&mut self) {
+        self.start = (self.index, self.line, self.column);
+    }
+
     fn add_simple_token(&mut self, kind: TokenKind) {
-        let start_index = self.index;
+        self.begin();
         self.advance();
-        self.add_token(kind, start_index);
+        self.add_token(kind);
     }
 
-    fn add_token(&mut self, kind: TokenKind, start_index: usize) {
-        let lexem = &self.input[start_index..self.index];
+    fn add_token(&mut self, kind: TokenKind) {
+        let (start_index, line, column) = self.start;
         self.tokens.push(Token {
             kind,
-            lexem,
-            line: self.line,
-            column: self.column - lexem.chars().count(),
-            index: start_index,
+            lexem: &self.input[start_index..self.index],
+            line,
+            column,
+            span: Span::new(start_index, self.index),
+            leading: std::mem::take(&mut self.trivia),
+            trailing: Vec::new(),
         });
     }
 
+    fn mark(&self) -> (usize, usize, usize) {
+        (self.index, self.line, self.column)
+    }
+
+    /// Moves back to `mark` so the error is reported where the problem starts.
+    fn fail_at(&mut self, mark: (usize, usize, usize), msg: String) -> Result<(), String> {
+        (self.index, self.line, self.column) = mark;
+        Err(msg)
+    }
+
     fn scan_string(&mut self) -> Result<(), String> {
+        self.begin();
+        if self.input[self.index..].starts_with("\"\"\"") {
+            return self.scan_raw_string();
+        }
         // Skip the opening quote
-        let start_index = self.index;
         self.advance();
 
         // Collect the string content
         let mut content = String::new();
         loop {
             match self.current_char {
-                None => return Err("Unterminated string literal".to_string()),
+                None => return self.fail_at(self.start, "Unterminated string literal".to_string()),
                 Some('"') => {
                     self.advance(); // Skip the closing quote
                     break;
                 },
-                Some('/') => {
-                    self.advance(); // Skip the slash
-                    match self.current_char {
-                        Some('n') => content.push('\n'),
-                        Some('r') => content.push('\r'),
-                        Some('t') => content.push('\t'),
-                        Some('/') => content.push('/'),
-                        Some('"') => content.push('"'),
-                        Some(c) => content.push(c),
-                        None => return Err("Escape at end of string".to_string()),
-                    }
+                Some('\\') => {
+                    let mark = self.mark();
+                    self.advance(); // Skip the backslash
+                    let c = match self.current_char {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('0') => '\0',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('"') => '"',
+                        Some('u') => {
+                            self.advance();
+                            match self.scan_unicode_escape() {
+                                Ok(c) => {
+                                    content.push(c);
+                                    continue;
+                                }
+                                Err(msg) => return self.fail_at(mark, msg),
+                            }
+                        }
+                        Some(c) => return self.fail_at(mark, format!("Invalid escape sequence '\\{}'", c)),
+                        None => return self.fail_at(mark, "Escape at end of string".to_string()),
+                    };
+                    content.push(c);
                     self.advance();
                 },
                 Some(c) => {
@@ -109,11 +168,89 @@ impl<'a> Lexer<'a> {
             }
         }
 
-        self.add_token(TokenKind::StringLiteral(content), start_index);
+        self.add_token(TokenKind::StringLiteral(content));
+        Ok(())
+    }
+
+    /// Reads the rest of a `\u` escape: `XXXX` (pairing surrogates) or `{X...}`.
+    fn scan_unicode_escape(&mut self) -> Result<char, String> {
+        if self.current_char == Some('{') {
+            self.advance();
+            let start = self.index;
+            while self.current_char.is_some_and(|c| c.is_ascii_hexdigit()) {
+                self.advance();
+            }
+            let digits = &self.input[start..self.index];
+            if self.current_char != Some('}') || digits.is_empty() || digits.len() > 6 {
+                return Err("Expected 1 to 6 hex digits in '\\u{...}'".to_string());
+            }
+            self.advance();
+            let code = u32::from_str_radix(digits, 16).unwrap();
+            return char::from_u32(code).ok_or(format!("Invalid unicode scalar value '\\u{{{}}}'", digits));
+        }
+
+        let high = self.scan_hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or(format!("Unpaired surrogate '\\u{:04X}'", high));
+        }
+        if !self.input[self.index..].starts_with("\\u") {
+            return Err(format!("Unpaired surrogate '\\u{:04X}'", high));
+        }
+        self.advance();
+        self.advance();
+        let low = self.scan_hex4()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(format!("Unpaired surrogate '\\u{:04X}'", high));
+        }
+        Ok(char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).unwrap())
+    }
+
+    fn scan_hex4(&mut self) -> Result<u32, String> {
+        let digits = self.input[self.index..].get(..4).filter(|d| d.chars().all(|c| c.is_ascii_hexdigit()));
+        let Some(digits) = digits else {
+            return Err("Expected 4 hex digits after '\\u'".to_string());
+        };
+        let code = u32::from_str_radix(digits, 16).unwrap();
+        for _ in 0..4 {
+            self.advance();
+        }
+        Ok(code)
+    }
+
+    /// A raw string opens with three or more quotes and ends at the same
+    /// number of quotes. Nothing inside is escaped, and a newline right after
+    /// the opening quotes is dropped.
+    fn scan_raw_string(&mut self) -> Result<(), String> {
+        let fence = self.input[self.index..].len() - self.input[self.index..].trim_start_matches('"').len();
+        for _ in 0..fence {
+            self.advance();
+        }
+        if self.current_char == Some('\r') && self.input[self.index..].starts_with("\r\n") {
+            self.advance();
+        }
+        if self.current_char == Some('\n') {
+            self.advance();
+        }
+
+        let quotes = &self.input[self.start.0..self.start.0 + fence];
+        let Some(mut length) = self.input[self.index..].find(quotes) else {
+            return self.fail_at(self.start, "Unterminated raw string literal".to_string());
+        };
+        // Quotes right before the closing ones belong to the content.
+        while self.input[self.index + length + fence..].starts_with('"') {
+            length += 1;
+        }
+        let content = self.input[self.index..self.index + length].to_string();
+        let end = self.index + length + fence;
+        while self.index < end {
+            self.advance();
+        }
+        self.add_token(TokenKind::StringLiteral(content));
         Ok(())
     }
 
     fn scan_number(&mut self) -> Result<(), String> {
+        self.begin();
         let start_index = self.index;
         let mut has_decimal = false;
 
@@ -137,12 +274,12 @@ impl<'a> Lexer<'a> {
         // Parse as integer or float
         if has_decimal {
             match lexem.parse::<f64>() {
-                Ok(value) => self.add_token(TokenKind::FloatLiteral(value), start_index),
+                Ok(value) => self.add_token(TokenKind::FloatLiteral(value)),
                 Err(_) => return Err(format!("Invalid float literal: {}", lexem)),
             }
         } else {
             match lexem.parse::<i64>() {
-                Ok(value) => self.add_token(TokenKind::IntegerLiteral(value), start_index),
+                Ok(value) => self.add_token(TokenKind::IntegerLiteral(value)),
                 Err(_) => return Err(format!("Invalid integer literal: {}", lexem)),
             }
         }
@@ -151,6 +288,7 @@ impl<'a> Lexer<'a> {
     }
 
     fn scan_symbol_or_keyword(&mut self) -> Result<(), String> {
+        self.begin();
         let start_index = self.index;
 
         // Collect the symbol characters
@@ -166,32 +304,61 @@ impl<'a> Lexer<'a> {
 
         // Check for keywords
         match lexem {
-            "true" => self.add_token(TokenKind::True, start_index),
-            "false" => self.add_token(TokenKind::False, start_index),
-            "null" => self.add_token(TokenKind::Null, start_index),
-            _ => self.add_token(TokenKind::Symbol(lexem.to_string()), start_index),
+            "true" => self.add_token(TokenKind::True),
+            "false" => self.add_token(TokenKind::False),
+            "null" => self.add_token(TokenKind::Null),
+            _ => self.add_token(TokenKind::Symbol(lexem.to_string())),
         }
 
         Ok(())
     }
 
+    /// Scans a `\\` line comment, a `\\\` doc-comment or a `\* *\` block
+    /// comment. Comments on the same line as the previous token trail it,
+    /// the others (and every doc-comment) lead the next token.
     fn comment(&mut self) -> Result<(), String> {
-        _ = self.advance();
-        match self.current_char {
+        self.begin();
+        self.advance();
+        let kind = match self.current_char {
             Some('\\') => {
-                _ = self.advance();
-                while let Some(c) = self.current_char {
-                    if c == '\n' {
-                        _ = self.advance();
-                        break;
-                    }
-                    _ = self.advance();
+                let rest = &self.input[self.index..];
+                let kind = if rest.starts_with("\\\\") && !rest.starts_with("\\\\\\") {
+                    TriviaKind::DocComment
+                } else {
+                    TriviaKind::LineComment
+                };
+                while self.current_char.is_some_and(|c| c != '\n') {
+                    self.advance();
+                }
+                kind
+            }
+            Some('*') => {
+                self.advance();
+                let Some(length) = self.input[self.index..].find("*\\") else {
+                    return self.fail_at(self.start, "Unterminated block comment".to_string());
+                };
+                let end = self.index + length + 2;
+                while self.index < end {
+                    self.advance();
                 }
-                Ok(())
+                TriviaKind::BlockComment
+            }
+            Some(c) => return Err(format!("Unexpected character: '{}'.", c)),
+            None => return Err("Unexpected enf of file.".into()),
+        };
+
+        let start = self.start.0;
+        let trivia = Trivia { kind, text: &self.input[start..self.index], span: Span::new(start, self.index) };
+        match self.tokens.last_mut() {
+            Some(last) if kind != TriviaKind::DocComment
+                && self.trivia.is_empty()
+                && !self.input[last.span.end..start].contains('\n') =>
+            {
+                last.trailing.push(trivia);
             }
-            Some(c) => Err(format!("Unexpected character: '{}'.", c).into()),
-            None => Err("Unexpected enf of file.".into())
+            _ => self.trivia.push(trivia),
         }
+        Ok(())
     }
 
     pub fn scan(&mut self) -> Result<(), String> {
@@ -209,6 +376,13 @@ impl<'a> Lexer<'a> {
                 '(' => self.add_simple_token(TokenKind::CloseExpr),
                 ':' => self.add_simple_token(TokenKind::Colon),
                 ',' => self.add_simple_token(TokenKind::Comma),
+                '.' if self.input[self.index..].starts_with("...") => {
+                    self.begin();
+                    for _ in 0..3 {
+                        self.advance();
+                    }
+                    self.add_token(TokenKind::Spread);
+                }
                 '\\' => self.comment()?,
 
                 // String literals
@@ -225,6 +399,10 @@ impl<'a> Lexer<'a> {
             }
         }
 
+        // Comments after the last token have nothing to lead.
+        if let Some(last) = self.tokens.last_mut() {
+            last.trailing.append(&mut self.trivia);
+        }
         Ok(())
     }
 }
@@ -0,0 +1,35 @@
+pub mod lexer;
+pub mod parser;
+pub mod token;
+pub mod span;
+pub mod ast;
+pub mod environment;
+pub mod runtime;
+pub mod vm;
+pub mod optimize;
+pub mod limits;
+pub mod json;
+pub mod emit;
+pub mod binary;
+pub mod io;
+pub mod query;
+pub mod pattern;
+pub mod schema;
+pub mod docs;
+pub mod cst;
+pub mod lint;
+pub mod diff;
+pub mod patch;
+pub mod repl;
+pub mod lsp;
+#[cfg(feature = "serde")]
+pub mod ser;
+#[cfg(feature = "serde")]
+pub mod de;
+
+pub use ast::{AsonNumber, AsonValue};
+pub use environment::Environment;
+#[cfg(feature = "serde")]
+pub use de::{from_str, Error};
+#[cfg(feature = "serde")]
+pub use ser::to_string;
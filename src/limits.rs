@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LimitKind {
+    Depth,
+    Steps,
+    StringSize,
+    ArraySize,
+    FileReadBytes,
+    Timeout,
+}
+
+/// Upper bounds enforced while parsing and evaluating a document.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    pub max_depth: usize,
+    pub max_steps: u64,
+    pub max_string_size: usize,
+    pub max_array_size: usize,
+    pub max_file_read_bytes: u64,
+    pub timeout: Option<Duration>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_depth: 256,
+            max_steps: 1_000_000,
+            max_string_size: 16 * 1024 * 1024,
+            max_array_size: 1_000_000,
+            max_file_read_bytes: 16 * 1024 * 1024,
+            timeout: None,
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::cst::{CstError, Document, Node, NodeKind};
+use crate::environment::Environment;
+use crate::parser::{tokenize, ParserError};
+use crate::span::{LineIndex, Span};
+use crate::token::{Token, TokenKind};
+
+/// Every rule with what it looks for.
+pub const RULES: &[(&str, &str)] = &[
+    ("unused-defvar", "a defvar binding that is never used"),
+    ("shadowing", "a defvar redefining an earlier binding or a builtin"),
+    ("mixed-separators", "an object using the optional ':' or ',' for some members only"),
+    ("constant-expression", "arithmetic on literals that could be a literal itself"),
+    ("side-effect", "write-line or write-error inside the data"),
+    ("division-by-zero", "division by a literal zero"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Off,
+    Warning,
+    Error,
+}
+
+/// The level of each rule; every rule is a warning unless changed.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    levels: HashMap<String, Level>,
+}
+
+impl LintConfig {
+    pub fn set(&mut self, rule: &str, level: Level) -> Result<(), String> {
+        if !RULES.iter().any(|(name, _)| *name == rule) {
+            return Err(format!("unknown lint rule '{}'", rule));
+        }
+        self.levels.insert(rule.to_string(), level);
+        Ok(())
+    }
+
+    pub fn level(&self, rule: &str) -> Level {
+        self.levels.get(rule).copied().unwrap_or(Level::Warning)
+    }
+}
+
+/// One finding, located like a parser error.
+#[derive(Debug)]
+pub struct Lint {
+    pub rule: &'static str,
+    pub level: Level,
+    pub error: ParserError,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]", self.error, self.rule)
+    }
+}
+
+/// Checks `source` without evaluating it, returning the findings in source order.
+pub fn lint(source: &str, file: &str, config: &LintConfig) -> Result<Vec<Lint>, CstError> {
+    let document = Document::parse(source)?;
+    // The document parsed, so the source lexes.
+    let tokens = tokenize(source, file).unwrap_or_default();
+    let mut linter = Linter {
+        source,
+        file,
+        config,
+        lines: LineIndex::new(source),
+        tokens: &tokens,
+        definitions: HashMap::new(),
+        builtins: Environment::new().symbols.into_keys().collect(),
+        lints: Vec::new(),
+    };
+    linter.node(document.root());
+    linter.unused();
+    linter.lints.sort_by_key(|l| l.error.span.start);
+    Ok(linter.lints)
+}
+
+enum Expr<'t> {
+    Literal(&'t Token<'t>),
+    Symbol(&'t Token<'t>),
+    Call { args: Vec<Expr<'t>>, callee: Option<&'t Token<'t>>, span: Span },
+}
+
+struct Linter<'s, 't> {
+    source: &'s str,
+    file: &'s str,
+    config: &'s LintConfig,
+    lines: LineIndex<'s>,
+    tokens: &'t [Token<'t>],
+    /// The latest `defvar` of each name.
+    definitions: HashMap<String, &'t Token<'t>>,
+    builtins: Vec<String>,
+    lints: Vec<Lint>,
+}
+
+impl<'t> Linter<'_, 't> {
+    fn report(&mut self, rule: &'static str, span: Span, msg: String) {
+        let level = self.config.level(rule);
+        if level == Level::Off {
+            return;
+        }
+        let (line, column) = self.lines.line_col(span.start);
+        let error = ParserError::new(msg, self.file.to_string(), line, column, span);
+        self.lints.push(Lint { rule, level, error });
+    }
+
+    fn node(&mut self, node: &Node) {
+        match &node.kind {
+            NodeKind::Object { members, commas } => {
+                // A spread never has a ':'.
+                let keyed = || members.iter().filter(|m| !m.spread);
+                if keyed().any(|m| m.colon) && !keyed().all(|m| m.colon) {
+                    self.report("mixed-separators", node.span, "Only some members of this object use ':'.".into());
+                }
+                // The last member may or may not have a trailing comma.
+                let separated = &members[..members.len().saturating_sub(1)];
+                if *commas && separated.iter().any(|m| m.comma.is_none()) {
+                    self.report("mixed-separators", node.span, "Only some members of this object are separated with ','.".into());
+                }
+                for member in members {
+                    if member.computed {
+                        self.expr_at(member.key_span);
+                    }
+                    self.node(&member.value);
+                }
+            }
+            NodeKind::Array { elements, .. } => {
+                for element in elements {
+                    self.node(&element.value);
+                }
+            }
+            NodeKind::Expr => self.expr_at(node.span),
+            NodeKind::Spread(value) => self.node(value),
+            NodeKind::Value => {}
+        }
+    }
+
+    /// Checks the expression-s at `span`, if that is one.
+    fn expr_at(&mut self, span: Span) {
+        let start = self.tokens.partition_point(|t| t.span.start < span.start);
+        if self.tokens.get(start).is_some_and(|t| t.kind == TokenKind::OpenExpr) {
+            let (expr, _) = self.expr(start);
+            self.check(&expr, true);
+        }
+    }
+
+    /// Reads the expression-s whose `)` is token `i`, returning the index after its `(`.
+    fn expr(&self, i: usize) -> (Expr<'t>, usize) {
+        let tokens = self.tokens;
+        let mut args = Vec::new();
+        let mut j = i + 1;
+        while let Some(token) = tokens.get(j) {
+            match token.kind {
+                TokenKind::CloseExpr => break,
+                TokenKind::OpenExpr => {
+                    let (arg, next) = self.expr(j);
+                    args.push(arg);
+                    j = next;
+                    continue;
+                }
+                TokenKind::Symbol(_) => args.push(Expr::Symbol(token)),
+                _ => args.push(Expr::Literal(token)),
+            }
+            j += 1;
+        }
+        let callee = match args.last() {
+            Some(Expr::Symbol(token)) => Some(*token),
+            _ => None,
+        };
+        if callee.is_some() {
+            args.pop();
+        }
+        let end = tokens.get(j).map_or(tokens[j - 1].span, |t| t.span);
+        (Expr::Call { args, callee, span: tokens[i].span.to(end) }, j + 1)
+    }
+
+    /// `report_constant` is false inside an expression already reported as constant.
+    fn check(&mut self, expr: &Expr<'t>, report_constant: bool) {
+        let Expr::Call { args, callee: Some(callee), span } = expr else { return };
+        let name = callee.lexem;
+        let constant = report_constant && is_constant(expr);
+        if constant {
+            let msg = "This expression only uses literals and could be written as a literal.".into();
+            self.report("constant-expression", *span, msg);
+        }
+        match name {
+            "defvar" => self.define(args),
+            "write-line" | "write-error" => {
+                self.report("side-effect", callee.span, format!("`{}` writes output while the data is evaluated.", name));
+            }
+            "/" => {
+                for arg in args.iter().skip(1) {
+                    if let Expr::Literal(token) = arg
+                        && is_zero(token)
+                    {
+                        self.report("division-by-zero", token.span, "Division by zero.".into());
+                    }
+                }
+            }
+            _ => {}
+        }
+        for arg in args {
+            self.check(arg, report_constant && !constant);
+        }
+    }
+
+    fn define(&mut self, args: &[Expr<'t>]) {
+        let [_, Expr::Literal(token)] = args else { return };
+        let TokenKind::StringLiteral(name) = &token.kind else { return };
+        if let Some(previous) = self.definitions.insert(name.clone(), token) {
+            let msg = format!("\"{}\" shadows the definition at {}:{}.", name, previous.line, previous.column);
+            self.report("shadowing", token.span, msg);
+        } else if self.builtins.contains(name) {
+            self.report("shadowing", token.span, format!("\"{}\" shadows a builtin.", name));
+        }
+    }
+
+    /// Reports definitions whose name is never used as a symbol after them.
+    fn unused(&mut self) {
+        let mut definitions: Vec<(&String, &&Token)> = self.definitions.iter().collect();
+        definitions.sort_by_key(|(_, token)| token.span.start);
+        let unused: Vec<Span> = definitions
+            .into_iter()
+            .filter(|(name, token)| {
+                !self.tokens.iter().any(|t| t.span.start > token.span.start && matches!(&t.kind, TokenKind::Symbol(s) if s == *name))
+            })
+            .map(|(_, token)| token.span)
+            .collect();
+        for span in unused {
+            let name = &self.source[span.start..span.end];
+            self.report("unused-defvar", span, format!("{} is defined but never used.", name));
+        }
+    }
+}
+
+fn is_zero(token: &Token) -> bool {
+    match token.kind {
+        TokenKind::IntegerLiteral(i) => i == 0,
+        TokenKind::FloatLiteral(f) => f == 0.0,
+        _ => false,
+    }
+}
+
+/// Arithmetic on number literals only, that evaluates without dividing by zero.
+fn is_constant(expr: &Expr) -> bool {
+    match expr {
+        Expr::Literal(token) => matches!(token.kind, TokenKind::IntegerLiteral(_) | TokenKind::FloatLiteral(_)),
+        Expr::Symbol(_) => false,
+        Expr::Call { args, callee: Some(callee), .. } => {
+            let divides_by_zero = callee.lexem == "/" && args.iter().skip(1).any(|a| matches!(a, Expr::Literal(t) if is_zero(t)));
+            matches!(callee.lexem, "+" | "-" | "*" | "/") && !divides_by_zero && args.iter().all(is_constant)
+        }
+        Expr::Call { .. } => false,
+    }
+}
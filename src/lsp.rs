@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::ast::AsonValue;
+use crate::cst::{Document, Node, NodeKind};
+use crate::environment::Environment;
+use crate::io::{AsonIo, SystemIo};
+use crate::lexer::Lexer;
+use crate::limits::Limits;
+use crate::lint::{lint, Level, LintConfig};
+use crate::parser::ParserError;
+use crate::span::{LineIndex, Span};
+use crate::token::{Token, TokenKind, TokenList};
+
+/// Reads files like the command line does, but drops everything written to
+/// stdout and stderr since stdout carries the protocol.
+#[derive(Debug)]
+struct LspIo;
+
+impl AsonIo for LspIo {
+    fn write_stdout(&mut self, _s: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_stderr(&mut self, _s: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn read_to_string(&mut self, path: &Path, max: u64) -> io::Result<String> {
+        SystemIo.read_to_string(path, max)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        SystemIo.canonicalize(path)
+    }
+
+    fn var(&self, name: &str) -> Option<String> {
+        SystemIo.var(name)
+    }
+}
+
+/// Runs a language server speaking JSON-RPC over `input` and `output` until
+/// the client sends `exit`.
+pub fn run(mut input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let mut server = Server { documents: HashMap::new() };
+    while let Some(message) = read_message(&mut input)? {
+        let method = message.get("method").and_then(|m| m.as_str()).unwrap_or("");
+        if method == "exit" {
+            break;
+        }
+        for reply in server.handle(method, &message) {
+            write_message(&mut output, &reply)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<AsonValue>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let Some(length) = length else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header"));
+    };
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    let body = String::from_utf8(body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    AsonValue::from_json_string(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+fn write_message(output: &mut impl Write, message: &AsonValue) -> io::Result<()> {
+    let body = message.to_json();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+fn object(members: Vec<(&str, AsonValue)>) -> AsonValue {
+    AsonValue::Object(members.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+}
+
+fn response(id: &AsonValue, result: AsonValue) -> AsonValue {
+    object(vec![("jsonrpc", "2.0".into()), ("id", id.clone()), ("result", result)])
+}
+
+fn position((line, character): (usize, usize)) -> AsonValue {
+    object(vec![("line", (line as i64).into()), ("character", (character as i64).into())])
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let mut decoded = Vec::new();
+    let bytes = path.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = path.get(i + 1..i + 3).and_then(|h| u8::from_str_radix(h, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+struct Server {
+    documents: HashMap<String, String>,
+}
+
+/// Everything known about one version of a document.
+struct Analysis<'a> {
+    uri: &'a str,
+    text: &'a str,
+    lines: LineIndex<'a>,
+    tokens: TokenList<'a>,
+    env: Environment,
+    error: Option<ParserError>,
+}
+
+impl Server {
+    fn handle(&mut self, method: &str, message: &AsonValue) -> Vec<AsonValue> {
+        let id = message.get("id");
+        let params = &message["params"];
+        let uri = params.pointer("/textDocument/uri").and_then(|u| u.as_str()).unwrap_or("").to_string();
+
+        match method {
+            "initialize" => {
+                let capabilities = object(vec![
+                    ("textDocumentSync", 1.into()),
+                    ("hoverProvider", true.into()),
+                    ("definitionProvider", true.into()),
+                    ("completionProvider", object(vec![])),
+                    ("documentSymbolProvider", true.into()),
+                ]);
+                let info = object(vec![("name", "ason".into())]);
+                let result = object(vec![("capabilities", capabilities), ("serverInfo", info)]);
+                id.map(|id| response(id, result)).into_iter().collect()
+            }
+            "shutdown" => id.map(|id| response(id, AsonValue::Null)).into_iter().collect(),
+            "textDocument/didOpen" => {
+                let text = params.pointer("/textDocument/text").and_then(|t| t.as_str()).unwrap_or("");
+                self.documents.insert(uri.clone(), text.to_string());
+                vec![self.diagnostics(&uri)]
+            }
+            "textDocument/didChange" => {
+                let changes = params["contentChanges"].as_array().map(|c| c.as_slice()).unwrap_or(&[]);
+                if let Some(text) = changes.last().and_then(|c| c["text"].as_str()) {
+                    self.documents.insert(uri.clone(), text.to_string());
+                }
+                vec![self.diagnostics(&uri)]
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                let params = object(vec![("uri", uri.as_str().into()), ("diagnostics", AsonValue::Array(vec![]))]);
+                vec![object(vec![("jsonrpc", "2.0".into()), ("method", "textDocument/publishDiagnostics".into()), ("params", params)])]
+            }
+            "textDocument/hover" | "textDocument/definition" | "textDocument/completion" | "textDocument/documentSymbol" => {
+                let Some(id) = id else { return vec![] };
+                let Some(text) = self.documents.get(&uri) else { return vec![response(id, AsonValue::Null)] };
+                let mut analysis = Analysis::new(&uri, text);
+                let at = analysis.lines.utf16_offset(
+                    params.pointer("/position/line").and_then(|v| v.as_i64()).unwrap_or(0) as usize,
+                    params.pointer("/position/character").and_then(|v| v.as_i64()).unwrap_or(0) as usize,
+                );
+                let result = match method {
+                    "textDocument/hover" => analysis.hover(at),
+                    "textDocument/definition" => analysis.definition(at),
+                    "textDocument/completion" => analysis.completion(),
+                    // The outline needs the document to parse; it is empty until then.
+                    _ => match Document::parse(text) {
+                        Ok(document) => AsonValue::Array(analysis.document_symbols(document.root())),
+                        Err(_) => AsonValue::Array(vec![]),
+                    },
+                };
+                vec![response(id, result)]
+            }
+            _ => match id {
+                Some(id) => {
+                    let error = object(vec![("code", (-32601).into()), ("message", format!("Unknown method {}", method).into())]);
+                    vec![object(vec![("jsonrpc", "2.0".into()), ("id", id.clone()), ("error", error)])]
+                }
+                None => vec![],
+            },
+        }
+    }
+
+    fn diagnostics(&self, uri: &str) -> AsonValue {
+        let text = self.documents.get(uri).map(|t| t.as_str()).unwrap_or("");
+        let analysis = Analysis::new(uri, text);
+        let mut diagnostics = Vec::new();
+        let errors = analysis.error.iter().map(|e| (e, 1));
+        for (e, severity) in errors.chain(analysis.env.warnings.iter().map(|w| (w, 2))) {
+            let mut span = e.span;
+            if span.is_empty() {
+                span.end += text[span.start..].chars().next().map_or(0, char::len_utf8);
+            }
+            diagnostics.push(object(vec![
+                ("range", analysis.range(span)),
+                ("severity", severity.into()),
+                ("source", "ason".into()),
+                ("message", e.msg.as_str().into()),
+            ]));
+        }
+        for lint in lint(text, uri, &LintConfig::default()).unwrap_or_default() {
+            let severity = if lint.level == Level::Error { 1 } else { 2 };
+            diagnostics.push(object(vec![
+                ("range", analysis.range(lint.error.span)),
+                ("severity", severity.into()),
+                ("source", "ason".into()),
+                ("code", lint.rule.into()),
+                ("message", lint.error.msg.as_str().into()),
+            ]));
+        }
+        let params = object(vec![("uri", uri.into()), ("diagnostics", AsonValue::Array(diagnostics))]);
+        object(vec![("jsonrpc", "2.0".into()), ("method", "textDocument/publishDiagnostics".into()), ("params", params)])
+    }
+}
+
+impl<'a> Analysis<'a> {
+    fn new(uri: &'a str, text: &'a str) -> Self {
+        let limits = Limits { timeout: Some(Duration::from_secs(2)), ..Limits::default() };
+        let mut env = Environment::with_limits(limits);
+        env.io = Box::new(LspIo);
+
+        let mut lex = Lexer::new(text);
+        let lex_error = lex.scan().err().map(|msg| {
+            ParserError::new(msg, uri.into(), lex.line(), lex.column(), Span::new(lex.index(), lex.index()))
+        });
+        let tokens = lex.into_tokens();
+        let error = match lex_error {
+            Some(e) => Some(e),
+            None => {
+                let result = match uri_to_path(uri) {
+                    Some(path) => AsonValue::from_ason_file_in(text, &path, &mut env),
+                    None => AsonValue::from_ason_string_in(text, &mut env),
+                };
+                result.err()
+            }
+        };
+
+        Analysis { uri, text, lines: LineIndex::new(text), tokens, env, error }
+    }
+
+    fn range(&self, span: Span) -> AsonValue {
+        object(vec![("start", position(self.lines.utf16_position(span.start))), ("end", position(self.lines.utf16_position(span.end)))])
+    }
+
+    fn token_at(&self, offset: usize) -> Option<usize> {
+        self.tokens
+            .iter()
+            .position(|t| t.span.contains(offset))
+            .or_else(|| self.tokens.iter().position(|t| t.span.end == offset))
+    }
+
+    /// The innermost `)...(` around `offset`, as token indices.
+    fn enclosing_expr(&self, offset: usize) -> Option<(usize, usize)> {
+        let mut open = Vec::new();
+        let mut best: Option<(usize, usize)> = None;
+        for (i, token) in self.tokens.iter().enumerate() {
+            match token.kind {
+                TokenKind::OpenExpr => open.push(i),
+                TokenKind::CloseExpr => {
+                    if let Some(start) = open.pop()
+                        && self.tokens[start].span.start <= offset
+                        && offset <= token.span.end
+                        && best.is_none_or(|(s, _)| start > s)
+                    {
+                        best = Some((start, i));
+                    }
+                }
+                _ => {}
+            }
+        }
+        best
+    }
+
+    fn hover(&mut self, offset: usize) -> AsonValue {
+        let Some(i) = self.token_at(offset) else { return AsonValue::Null };
+        let token = &self.tokens[i];
+        let (span, contents) = match (&token.kind, self.enclosing_expr(offset)) {
+            (TokenKind::StringLiteral(_), _) if token.doc().is_some() => (token.span, token.doc().unwrap()),
+            (TokenKind::Symbol(name), _) if self.env.symbols.get(name).is_some_and(|v| !v.is_function()) => {
+                (token.span, format!("```json\n{}\n```", self.env.symbols[name].to_pretty_json(2)))
+            }
+            (_, Some((open, close))) => {
+                let span = self.tokens[open].span.to(self.tokens[close].span);
+                let contents = match AsonValue::from_ason_string_in(&self.text[span.start..span.end], &mut self.env) {
+                    Ok(v) => format!("```json\n{}\n```", v.to_pretty_json(2)),
+                    Err(e) => format!("Error: {}", e.msg),
+                };
+                (span, contents)
+            }
+            (TokenKind::Symbol(name), None) => match self.env.symbols.get(name) {
+                Some(_) => (token.span, format!("builtin function `{}`", name)),
+                None => (token.span, format!("undefined symbol `{}`", name)),
+            },
+            _ => return AsonValue::Null,
+        };
+        object(vec![
+            ("contents", object(vec![("kind", "markdown".into()), ("value", contents.into())])),
+            ("range", self.range(span)),
+        ])
+    }
+
+    fn definition(&self, offset: usize) -> AsonValue {
+        let Some(i) = self.token_at(offset) else { return AsonValue::Null };
+        let TokenKind::Symbol(ref name) = self.tokens[i].kind else { return AsonValue::Null };
+        let definitions: Vec<&Token> = self.tokens
+            .windows(2)
+            .filter(|w| matches!(&w[0].kind, TokenKind::StringLiteral(s) if s == name))
+            .filter(|w| matches!(&w[1].kind, TokenKind::Symbol(s) if s == "defvar"))
+            .map(|w| &w[0])
+            .collect();
+        // Prefer the closest definition before the use.
+        let definition = definitions.iter().rev().find(|t| t.span.start < offset).or(definitions.first());
+        match definition {
+            Some(t) => object(vec![("uri", self.uri.into()), ("range", self.range(t.span))]),
+            None => AsonValue::Null,
+        }
+    }
+
+    fn completion(&self) -> AsonValue {
+        let mut names: Vec<&String> = self.env.symbols.keys().collect();
+        names.sort();
+        let items = names
+            .into_iter()
+            .map(|name| {
+                let (kind, detail) = match &self.env.symbols[name] {
+                    AsonValue::Function(_) => (3, "function".to_string()),
+                    value => (6, value.to_json()),
+                };
+                object(vec![("label", name.as_str().into()), ("kind", kind.into()), ("detail", detail.into())])
+            })
+            .collect();
+        AsonValue::Array(items)
+    }
+
+    /// Lists the object members inside `node` as nested document symbols.
+    /// Arrays only show up, by index, when they hold objects.
+    fn document_symbols(&self, node: &Node) -> Vec<AsonValue> {
+        match &node.kind {
+            NodeKind::Object { members, .. } => {
+                let mut symbols = Vec::new();
+                for member in members {
+                    // The members of a spread object are listed with the others.
+                    if member.spread {
+                        symbols.extend(self.document_symbols(&member.value));
+                        continue;
+                    }
+                    // Computed keys are named as they are written.
+                    let name = match member.computed {
+                        true => &self.text[member.key_span.start..member.key_span.end],
+                        false => member.key.as_str(),
+                    };
+                    let span = member.key_span.to(member.value.span);
+                    symbols.push(self.symbol(name, &member.value, member.key_span, span, self.document_symbols(&member.value)));
+                }
+                symbols
+            }
+            NodeKind::Array { elements, .. } => elements
+                .iter()
+                .enumerate()
+                .filter_map(|(index, element)| {
+                    let value = &element.value;
+                    let children = self.document_symbols(value);
+                    (!children.is_empty()).then(|| self.symbol(&index.to_string(), value, value.span, value.span, children))
+                })
+                .collect(),
+            NodeKind::Spread(value) => self.document_symbols(value),
+            NodeKind::Value | NodeKind::Expr => Vec::new(),
+        }
+    }
+
+    /// `key` is where the name is written; its first token carries the doc-comment.
+    fn symbol(&self, name: &str, value: &Node, key: Span, span: Span, children: Vec<AsonValue>) -> AsonValue {
+        let token = |span: Span| self.token_at(span.start).map(|i| &self.tokens[i]);
+        let kind = match token(value.span).map(|t| &t.kind) {
+            Some(TokenKind::OpenObject) => 19,
+            Some(TokenKind::OpenArray) => 18,
+            Some(TokenKind::StringLiteral(_)) => 15,
+            Some(TokenKind::IntegerLiteral(_) | TokenKind::FloatLiteral(_)) => 16,
+            Some(TokenKind::True | TokenKind::False) => 17,
+            Some(TokenKind::Null) => 21,
+            _ => 13,
+        };
+        let key = token(key);
+        object(vec![
+            ("name", name.into()),
+            ("kind", (kind as i64).into()),
+            ("range", self.range(span)),
+            ("selectionRange", self.range(key.map_or(span, |t| t.span))),
+            ("detail", key.and_then(|t| t.doc()).map_or(AsonValue::Null, AsonValue::from)),
+            ("children", AsonValue::Array(children)),
+        ])
+    }
+}
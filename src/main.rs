@@ -1,31 +1,402 @@
-use std::path::Path;
-use std::io::Read;
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::io::{Read, Write};
 use std::fs::File;
 use std::process::exit;
 
-use ast::AsonValue;
+use ason::{AsonValue, Environment};
+use ason::query::query;
+use ason::schema::Schema;
+use ason::cst::Document;
+use ason::span::LineIndex;
+use ason::diff::diff;
+use ason::{lsp, repl};
+use ason::parser::DuplicateKeys;
+use ason::lexer::Lexer;
+use ason::token::TokenKind;
+use ason::lint::{lint, Level, LintConfig};
+use ason::emit::{emitter, Emitter, Json, FORMATS};
+use ason::optimize::partially_evaluate;
+use ason::binary::{from_cbor, from_msgpack, to_cbor, to_msgpack};
 
-mod lexer;
-mod parser;
-mod token;
-mod ast;
-mod environment;
-mod runtime;
+const USAGE: &str = "usage: ason [eval] [--stream] [--format json|canonical-json|yaml|toml|ason] [--set name=value]... [--var-file vars.json]... [--duplicate-keys policy] [file.ason]
+       ason query <expr> [options] [file.ason]
+       ason validate --schema <schema.json> [options] [file.ason]
+       ason diff [options] <old.ason> <new.ason>
+       ason encode msgpack|cbor [options] [file.ason]
+       ason decode msgpack|cbor [--format format] <file>
+       ason simplify [--set name=value]... [--var-file vars.json]... [file.ason]
+       ason lint [--rule name=off|warn|error]... [file.ason]
+       ason repl [options]
+       ason lsp";
+
+enum Command {
+    Eval,
+    Query(String),
+    Validate,
+    Diff,
+    Simplify,
+    Encode(Binary),
+    Decode(Binary),
+    Lint,
+    Repl,
+    Lsp,
+}
+
+enum Binary {
+    MessagePack,
+    Cbor,
+}
+
+struct Options {
+    command: Command,
+    files: Vec<PathBuf>,
+    schema: Option<PathBuf>,
+    sets: Vec<(String, String)>,
+    var_files: Vec<PathBuf>,
+    duplicate_keys: DuplicateKeys,
+    stream: bool,
+    format: Option<Box<dyn Emitter>>,
+    lint: LintConfig,
+}
 
 fn main() {
-    let content = read_file(&Path::new("./test.ason"));
-    let value = match AsonValue::from_ason_string(&content) {
+    let options = parse_args(env::args().skip(1));
+
+    let default_file = PathBuf::from("./test.ason");
+    let file = options.files.first().unwrap_or(&default_file);
+    match options.command {
+        Command::Eval if options.stream => {
+            let mut env = environment(&options);
+            let content = read_file(file);
+            for document in AsonValue::file_documents_in(&content, file, &mut env) {
+                match document {
+                    Ok(v) => match &options.format {
+                        Some(format) => println!("{}", emit(format.as_ref(), &v, file)),
+                        None => println!("{}", v.to_json()),
+                    },
+                    Err(e) => {
+                        e.report();
+                        exit(1);
+                    }
+                }
+            }
+        }
+        Command::Eval => {
+            let format = options.format.as_deref().unwrap_or(&Json);
+            println!("{}", emit(format, &evaluate(file, &options), file));
+        }
+        Command::Query(ref expr) => match query(&evaluate(file, &options), expr) {
+            Ok(results) => {
+                for result in results {
+                    println!("{}", result.to_json());
+                }
+            }
+            Err(e) => fail(&format!("in query {}", e)),
+        },
+        Command::Validate => {
+            let Some(schema) = &options.schema else { fail("validate expects --schema") };
+            let schema = Schema::new(load_value(schema))
+                .unwrap_or_else(|e| fail(&format!("{}: {}", schema.display(), e)));
+            let source = read_file(file);
+            let value = evaluate(file, &options);
+            let violations = match Document::parse(&source) {
+                Ok(document) => schema.validate_document(&value, &document),
+                Err(_) => schema.validate(&value),
+            };
+            let lines = LineIndex::new(&source);
+            for violation in &violations {
+                match violation.span {
+                    Some(span) => {
+                        let (line, column) = lines.line_col(span.start);
+                        eprintln!("Error {}:{}:{}: {}", file.display(), line, column, violation);
+                    }
+                    None => eprintln!("Error {}: {}", file.display(), violation),
+                }
+            }
+            if !violations.is_empty() {
+                exit(1);
+            }
+        }
+        Command::Diff => {
+            let [old, new] = options.files.as_slice() else { fail("diff expects two files") };
+            let changes = diff(&evaluate(old, &options), &evaluate(new, &options));
+            for change in &changes {
+                println!("{}", change);
+            }
+            if !changes.is_empty() {
+                exit(1);
+            }
+        }
+        Command::Simplify => {
+            // Only the inputs given on the command line are known.
+            let known = environment(&options).symbols.into_iter().filter(|(_, v)| !v.is_function()).collect();
+            match partially_evaluate(&read_file(file), known) {
+                Ok(simplified) => print!("{}", simplified),
+                Err(e) => {
+                    eprintln!("Error {}:{}", file.display(), e);
+                    exit(1);
+                }
+            }
+        }
+        Command::Encode(ref binary) => {
+            let value = evaluate(file, &options);
+            let bytes = match binary {
+                Binary::MessagePack => to_msgpack(&value),
+                Binary::Cbor => to_cbor(&value),
+            };
+            if let Err(e) = io::stdout().write_all(&bytes) {
+                fail(&e.to_string());
+            }
+        }
+        Command::Decode(ref binary) => {
+            let bytes = std::fs::read(file).unwrap_or_else(|e| fail(&format!("{}: {}", file.display(), e)));
+            let decoded = match binary {
+                Binary::MessagePack => from_msgpack(&bytes),
+                Binary::Cbor => from_cbor(&bytes),
+            };
+            let value = decoded.unwrap_or_else(|e| {
+                eprintln!("Error {}: {}", file.display(), e);
+                exit(1);
+            });
+            let format = options.format.as_deref().unwrap_or(&Json);
+            println!("{}", emit(format, &value, file));
+        }
+        Command::Lint => {
+            let lints = lint(&read_file(file), &file.display().to_string(), &options.lint).unwrap_or_else(|e| {
+                eprintln!("Error {}:{}", file.display(), e);
+                exit(1);
+            });
+            for lint in &lints {
+                match lint.level {
+                    Level::Error => eprintln!("Error {}", lint),
+                    _ => eprintln!("Warning {}", lint),
+                }
+            }
+            if lints.iter().any(|l| l.level == Level::Error) {
+                exit(1);
+            }
+        }
+        Command::Lsp => {
+            if let Err(e) = lsp::run(io::stdin().lock(), io::stdout()) {
+                fail(&e.to_string());
+            }
+        }
+        Command::Repl => {
+            let mut env = environment(&options);
+            if let Err(e) = repl::run(&mut env, io::stdin().lock(), &mut io::stdout()) {
+                fail(&e.to_string());
+            }
+        }
+    }
+}
+
+/// Evaluates `path` in a fresh environment populated from the command-line inputs.
+fn evaluate(path: &Path, options: &Options) -> AsonValue {
+    let mut env = environment(options);
+    let content = read_file(path);
+    match AsonValue::from_ason_file_in(&content, path, &mut env) {
         Ok(v) => v,
         Err(e) => {
             e.report();
             exit(1);
         }
+    }
+}
+
+fn emit(format: &dyn Emitter, value: &AsonValue, path: &Path) -> String {
+    format.emit(value).unwrap_or_else(|e| {
+        eprintln!("Error {}: {}", path.display(), e);
+        exit(1);
+    })
+}
+
+fn environment(options: &Options) -> Environment {
+    let mut env = Environment::new();
+    env.duplicate_keys = options.duplicate_keys;
+    for var_file in &options.var_files {
+        load_var_file(&mut env, var_file);
+    }
+    for (name, value) in &options.sets {
+        env.add_constant(name.clone(), input_value(value));
+    }
+    env
+}
+
+/// Reads a `--set` value. A single number, boolean, `null` or quoted string
+/// literal keeps its type, anything else (symbols included) is a string.
+fn input_value(value: &str) -> AsonValue {
+    let mut lexer = Lexer::new(value);
+    if lexer.scan().is_ok()
+        && let [token] = lexer.into_tokens().as_slice()
+    {
+        match &token.kind {
+            TokenKind::IntegerLiteral(i) => return AsonValue::from(*i),
+            TokenKind::FloatLiteral(f) => return AsonValue::from(*f),
+            TokenKind::StringLiteral(s) => return AsonValue::from(s.as_str()),
+            TokenKind::True => return AsonValue::Boolean(true),
+            TokenKind::False => return AsonValue::Boolean(false),
+            TokenKind::Null => return AsonValue::Null,
+            _ => {}
+        }
+    }
+    AsonValue::String(value.into())
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Options {
+    let mut args = args.peekable();
+    let mut options = Options {
+        command: Command::Eval,
+        files: Vec::new(),
+        schema: None,
+        sets: Vec::new(),
+        var_files: Vec::new(),
+        duplicate_keys: DuplicateKeys::default(),
+        stream: false,
+        format: None,
+        lint: LintConfig::default(),
     };
-    println!("{}", value.to_pretty_json(2));
+
+    match args.peek().map(|a| a.as_str()) {
+        Some("eval") => {
+            args.next();
+        }
+        Some("query") => {
+            args.next();
+            match args.next() {
+                Some(expr) => options.command = Command::Query(expr),
+                None => fail("query expects an expression"),
+            }
+        }
+        Some("validate") => {
+            args.next();
+            options.command = Command::Validate;
+        }
+        Some("diff") => {
+            args.next();
+            options.command = Command::Diff;
+        }
+        Some("simplify") => {
+            args.next();
+            options.command = Command::Simplify;
+        }
+        Some(command @ ("encode" | "decode")) => {
+            let encode = command == "encode";
+            args.next();
+            let binary = match args.next().as_deref() {
+                Some("msgpack") => Binary::MessagePack,
+                Some("cbor") => Binary::Cbor,
+                _ => fail(&format!("{} expects msgpack or cbor", if encode { "encode" } else { "decode" })),
+            };
+            options.command = if encode { Command::Encode(binary) } else { Command::Decode(binary) };
+        }
+        Some("lint") => {
+            args.next();
+            options.command = Command::Lint;
+        }
+        Some("repl") => {
+            args.next();
+            options.command = Command::Repl;
+        }
+        Some("lsp") => {
+            args.next();
+            options.command = Command::Lsp;
+        }
+        _ => {}
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--set" => {
+                let Some(assignment) = args.next() else { fail("--set expects name=value") };
+                let Some((name, value)) = assignment.split_once('=') else { fail("--set expects name=value") };
+                options.sets.push((name.into(), value.into()));
+            }
+            "--var-file" => match args.next() {
+                Some(path) => options.var_files.push(path.into()),
+                None => fail("--var-file expects a path"),
+            },
+            "--stream" => options.stream = true,
+            "--format" => {
+                let format = args.next().unwrap_or_default();
+                match emitter(&format) {
+                    Some(emitter) => options.format = Some(emitter),
+                    None => fail(&format!("--format expects one of {}", FORMATS.join(", "))),
+                }
+            }
+            "--rule" => {
+                let Some((rule, level)) = args.next().and_then(|r| r.split_once('=').map(|(a, b)| (a.to_string(), b.to_string())))
+                else {
+                    fail("--rule expects name=off|warn|error")
+                };
+                let level = match level.as_str() {
+                    "off" => Level::Off,
+                    "warn" => Level::Warning,
+                    "error" => Level::Error,
+                    _ => fail("--rule expects name=off|warn|error"),
+                };
+                options.lint.set(&rule, level).unwrap_or_else(|e| fail(&e));
+            }
+            "--duplicate-keys" => {
+                options.duplicate_keys = match args.next().as_deref() {
+                    Some("error") => DuplicateKeys::Error,
+                    Some("warn") => DuplicateKeys::Warn,
+                    Some("last-wins") => DuplicateKeys::LastWins,
+                    Some("first-wins") => DuplicateKeys::FirstWins,
+                    _ => fail("--duplicate-keys expects error, warn, last-wins or first-wins"),
+                }
+            }
+            "--schema" => match args.next() {
+                Some(path) => options.schema = Some(path.into()),
+                None => fail("--schema expects a path"),
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                exit(0);
+            }
+            a if a.starts_with('-') => fail(&format!("unknown option '{}'", a)),
+            _ => options.files.push(arg.into()),
+        }
+    }
+
+    options
+}
+
+/// Reads a value from a side file. Files ending in `.ason` are evaluated,
+/// anything else is read as JSON.
+fn load_value(path: &Path) -> AsonValue {
+    let content = read_file(path);
+    if path.extension().is_some_and(|e| e == "ason") {
+        AsonValue::from_ason_file_in(&content, path, &mut Environment::new()).unwrap_or_else(|e| {
+            e.report();
+            exit(1);
+        })
+    } else {
+        AsonValue::from_json_string(&content).unwrap_or_else(|e| fail(&format!("{}:{}", path.display(), e)))
+    }
+}
+
+/// Defines every member of the object in `path` as a constant.
+fn load_var_file(env: &mut Environment, path: &Path) {
+    match load_value(path) {
+        AsonValue::Object(members) => {
+            for (name, value) in members {
+                env.add_constant(name, value);
+            }
+        }
+        _ => fail(&format!("{}: variable file must contain an object", path.display())),
+    }
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("Error {}", msg);
+    eprintln!("{}", USAGE);
+    exit(1);
 }
 
 fn read_file(path: &Path) -> String {
-    let mut f = File::open(path).unwrap();
+    let mut f = File::open(path).unwrap_or_else(|e| fail(&format!("{}: {}", path.display(), e)));
     let mut text = String::new();
     _ = f.read_to_string(&mut text).unwrap();
     text
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::ast::{AsonExpr, AsonNumber, AsonValue};
+use crate::cst::{CstResult, Document, Node, NodeKind};
+use crate::environment::Environment;
+use crate::lexer::Lexer;
+use crate::parser::{tokenize, Parser};
+use crate::token::{Token, TokenKind};
+
+/// Builtins whose result only depends on their arguments.
+pub const PURE: &[&str] = &["+", "-", "*", "/", "concat"];
+
+/// Folds calls to pure builtins whose arguments are all known, and replaces
+/// symbols with the value they are known to have.
+pub struct Optimizer {
+    known: HashMap<String, AsonValue>,
+    env: Environment,
+    changed: bool,
+}
+
+impl Optimizer {
+    /// `known` holds the inputs the document will be evaluated with.
+    pub fn new(known: HashMap<String, AsonValue>) -> Self {
+        Optimizer { known, env: Environment::new(), changed: false }
+    }
+
+    /// Folds `expr`, learning the `defvar` constants it binds on the way.
+    /// Evaluation order is kept, so a binding only applies after it.
+    pub fn fold(&mut self, expr: &AsonExpr) -> AsonExpr {
+        match expr {
+            AsonExpr::Symbol(name, span) => match self.known.get(name) {
+                Some(value) => {
+                    self.changed = true;
+                    AsonExpr::Value(value.clone(), *span)
+                }
+                None => AsonExpr::Symbol(name.clone(), *span),
+            },
+            AsonExpr::ExprS(original, callee, span) => {
+                let args: Vec<AsonExpr> = original.iter().map(|a| self.fold(a)).collect();
+                let values: Option<Vec<AsonValue>> = args
+                    .iter()
+                    .map(|a| match a {
+                        AsonExpr::Value(value, _) => Some(value.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                match (callee.as_str(), values) {
+                    ("defvar", values) => match (values.as_deref(), args.as_slice()) {
+                        (Some([value, AsonValue::String(name)]), _) => _ = self.known.insert(name.clone(), value.clone()),
+                        // Only the named symbol changes, to something unknown.
+                        (None, [_, AsonExpr::Value(AsonValue::String(name), _)]) => _ = self.known.remove(name),
+                        // Anything may be bound, so nothing is known anymore.
+                        _ => self.known.clear(),
+                    },
+                    ("import", _) => self.known.clear(),
+                    (_, Some(values)) if !self.known.contains_key(callee) => {
+                        if let Some(value) = self.call(callee, &values) {
+                            self.changed = true;
+                            return AsonExpr::Value(value, *span);
+                        }
+                    }
+                    _ => {}
+                }
+                // Arguments can't be written as objects or arrays.
+                let args = args
+                    .into_iter()
+                    .zip(original)
+                    .map(|(folded, original)| match folded {
+                        AsonExpr::Value(AsonValue::Object(_) | AsonValue::Array(_), _) => original.clone(),
+                        folded => folded,
+                    })
+                    .collect();
+                AsonExpr::ExprS(args, callee.clone(), *span)
+            }
+            AsonExpr::Value(value, span) => AsonExpr::Value(value.clone(), *span),
+            AsonExpr::None(span) => AsonExpr::None(*span),
+        }
+    }
+
+    /// Calls a pure builtin, unless it would fail or give a value ASON can't
+    /// write back.
+    fn call(&mut self, callee: &str, args: &[AsonValue]) -> Option<AsonValue> {
+        if !PURE.contains(&callee) {
+            return None;
+        }
+        if callee != "concat" && (!args.iter().all(AsonValue::is_number) || overflows(callee, args)) {
+            return None;
+        }
+        match self.env.call_fn(callee, args.to_vec()).ok()? {
+            AsonValue::Number(AsonNumber::Float(f)) if !f.is_finite() => None,
+            value => Some(value),
+        }
+    }
+}
+
+/// Whether integer arithmetic in `args` overflows, which the builtins don't
+/// survive. Past the first float, the rest is float arithmetic.
+fn overflows(callee: &str, args: &[AsonValue]) -> bool {
+    let mut total: i64 = if callee == "*" { 1 } else { 0 };
+    for arg in args {
+        let Some(AsonNumber::Integer(i)) = arg.as_number() else { return false };
+        let next = match callee {
+            "+" => total.checked_add(*i),
+            "-" => total.checked_sub(*i),
+            "*" => total.checked_mul(*i),
+            _ => return false,
+        };
+        match next {
+            Some(next) => total = next,
+            None => return true,
+        }
+    }
+    false
+}
+
+/// Simplifies `source` with the inputs in `known`, folding what can be
+/// folded and keeping everything else, comments and layout included, as
+/// it is written.
+pub fn partially_evaluate(source: &str, known: HashMap<String, AsonValue>) -> CstResult<String> {
+    let document = Document::parse(source)?;
+    // The document parsed, so the source lexes.
+    let tokens = tokenize(source, "").unwrap_or_default();
+    let mut simplifier = Simplifier { source, tokens: &tokens, optimizer: Optimizer::new(known), edits: Vec::new() };
+    simplifier.node(document.root());
+
+    let mut simplified = source.to_string();
+    for (range, text) in simplifier.edits.into_iter().rev() {
+        simplified.replace_range(range, &text);
+    }
+    Ok(simplified)
+}
+
+struct Simplifier<'s, 't> {
+    source: &'s str,
+    tokens: &'t [Token<'t>],
+    optimizer: Optimizer,
+    /// Replacements in source order.
+    edits: Vec<(Range<usize>, String)>,
+}
+
+impl Simplifier<'_, '_> {
+    fn node(&mut self, node: &Node) {
+        match &node.kind {
+            NodeKind::Object { members, .. } => {
+                for member in members {
+                    if member.computed {
+                        self.expression(member.key_span.start..member.key_span.end);
+                    }
+                    self.node(&member.value);
+                }
+            }
+            NodeKind::Array { elements, .. } => {
+                for element in elements {
+                    self.node(&element.value);
+                }
+            }
+            NodeKind::Spread(value) => self.node(value),
+            NodeKind::Expr | NodeKind::Value => self.expression(node.span.start..node.span.end),
+        }
+    }
+
+    /// Folds the symbol or expression-s in `range`, if that is one.
+    fn expression(&mut self, range: Range<usize>) {
+        let start = self.tokens.partition_point(|t| t.span.start < range.start);
+        let Some(token) = self.tokens.get(start) else { return };
+        let expr = match &token.kind {
+            TokenKind::Symbol(name) => AsonExpr::Symbol(name.clone(), token.span),
+            TokenKind::OpenExpr => {
+                let mut lexer = Lexer::new(&self.source[range.clone()]);
+                if lexer.scan().is_err() {
+                    return;
+                }
+                let tokens = lexer.into_tokens();
+                let mut env = Environment::new();
+                match Parser::new(&tokens, String::new(), &mut env).expression() {
+                    Ok(expr) => expr,
+                    Err(_) => return,
+                }
+            }
+            _ => return,
+        };
+        self.optimizer.changed = false;
+        let folded = self.optimizer.fold(&expr);
+        if self.optimizer.changed {
+            self.edits.push((range, folded.to_ason()));
+        }
+    }
+}
@@ -1,55 +1,122 @@
+use core::fmt;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use crate::environment::Environment;
-use crate::runtime::RuntimeError;
+use crate::lexer::Lexer;
 use crate::token::{Token, TokenKind, TokenList};
 use crate::ast::{AsonExpr, AsonNumber, AsonValue};
+use crate::span::Span;
+use crate::vm::{Program, Vm};
 
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct ParserError {
-    msg: String,
-    file: String,
-    line: usize,
-    column: usize,
+    pub msg: String,
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
 }
 
 impl ParserError {
+    pub fn new(msg: String, file: String, line: usize, column: usize, span: Span) -> Self {
+        Self { msg, file, line, column, span }
+    }
+
     pub fn report(&self) {
-        eprintln!("Error ./{}:{}:{}: {}", self.file, self.line, self.column, self.msg);
+        eprintln!("Error {}", self);
+    }
+}
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}: {}", self.file, self.line, self.column, self.msg)
     }
 }
 
 pub type ParserResult<T> = Result<T, ParserError>;
 
+/// What to do when an object repeats a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeys {
+    Error,
+    /// Keeps the last value and reports a warning.
+    #[default]
+    Warn,
+    LastWins,
+    FirstWins,
+}
+
 #[derive(Debug)]
 pub struct Parser<'a> {
     tokens: &'a TokenList<'a>,
     current: usize,
-    env: Environment,
+    env: &'a mut Environment,
     file: String,
+    duplicate_keys: DuplicateKeys,
+    program: Program,
+    vm: Vm,
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(tokens: &'a TokenList<'a>, file: String) -> Self {
+    /// Creates a parser using the environment's duplicate-key policy.
+    pub fn new(tokens: &'a TokenList<'a>, file: String, env: &'a mut Environment) -> Self {
         Parser {
             tokens,
             file,
             current: 0,
-            env: Environment::new(),
+            duplicate_keys: env.duplicate_keys,
+            env,
+            program: Program::default(),
+            vm: Vm::new(),
         }
     }
 
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Parses a single value, which must be all there is in the input.
     pub fn parse(&mut self) -> ParserResult<AsonValue> {
-        self.parse_value()
+        if self.tokens.is_empty() {
+            return Err(ParserError::new("Expected a value.".into(), self.file.clone(), 1, 1, Span::default()));
+        }
+        let value = self.parse_value()?;
+        if !self.is_at_end() {
+            return Err(self.report(format!("Unexpected token after the value: {}", self.peek().lexem)));
+        }
+        Ok(value)
+    }
+
+    /// Parses a single expression-s without evaluating it or any of its
+    /// arguments.
+    pub fn expression(&mut self) -> ParserResult<AsonExpr> {
+        if self.tokens.is_empty() {
+            return Err(ParserError::new("Expected an expression-s.".into(), self.file.clone(), 1, 1, Span::default()));
+        }
+        if self.peek().kind != TokenKind::OpenExpr {
+            return Err(self.report("Expected an expression-s.".into()));
+        }
+        self.advance();
+        let expr = self.parse_expr_s()?;
+        if !self.is_at_end() {
+            return Err(self.report(format!("Unexpected token after the value: {}", self.peek().lexem)));
+        }
+        Ok(expr)
     }
 
     fn parse_value(&mut self) -> ParserResult<AsonValue> {
-        match self.advance().kind {
+        if self.is_at_end() {
+            return Err(self.report("Expected a value.".into()));
+        }
+        let token = self.advance();
+        match token.kind {
             TokenKind::OpenObject => self.parse_object(),
             TokenKind::OpenArray => self.parse_array(),
             TokenKind::OpenExpr => self.parse_expr(),
-            TokenKind::StringLiteral(ref v) => Ok(AsonValue::String(v.clone())),
+            TokenKind::StringLiteral(ref v) => self.check_size(AsonValue::String(v.clone())),
             TokenKind::IntegerLiteral(v) => Ok(AsonValue::Number(AsonNumber::Integer(v))),
             TokenKind::FloatLiteral(v) => Ok(AsonValue::Number(AsonNumber::Float(v))),
             TokenKind::True => Ok(AsonValue::Boolean(true)),
@@ -57,7 +124,7 @@ impl<'a> Parser<'a> {
             TokenKind::Null => Ok(AsonValue::Null),
             TokenKind::Symbol(ref id) => {
                 if !self.env.symbols.contains_key(id) {
-                    return Err(self.report(format!("Undefined symbol: {}", id)));
+                    return Err(self.report_at(token, token.span, format!("Undefined symbol: {}", id)));
                 }
                 let value = self.env.symbols.get(id).unwrap();
                 Ok(value.clone())
@@ -68,69 +135,141 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_object(&mut self) -> ParserResult<AsonValue> {
+        self.nested(Self::object)
+    }
+
+    fn object(&mut self) -> ParserResult<AsonValue> {
         let mut members = HashMap::<String, AsonValue>::new();
-        while !self.is_at_end() {
-            match self.advance().kind {
+        let mut keys = HashMap::<String, &Token>::new();
+        let open = &self.tokens[self.current - 1];
+        loop {
+            if self.is_at_end() {
+                return Err(self.report_at(open, open.span, "Unclosed object, expected '{'.".into()));
+            }
+            let token = self.advance();
+            match token.kind {
                 TokenKind::CloseObject => break,
-                TokenKind::StringLiteral(ref v) => {
-                    let key = v.clone();
-                    members.insert(key, self.parse_value()?);
+                TokenKind::StringLiteral(_) | TokenKind::Symbol(_) | TokenKind::OpenExpr => {
+                    let key = match token.kind {
+                        TokenKind::StringLiteral(ref key) => key.clone(),
+                        // Keys can also come from a symbol or an expression-s.
+                        _ => {
+                            self.current -= 1;
+                            match self.parse_value()? {
+                                AsonValue::String(key) => key,
+                                other => {
+                                    let span = token.span.to(self.tokens[self.current - 1].span);
+                                    let msg = format!("Object keys must be strings, got {}.", other.type_name());
+                                    return Err(self.report_at(token, span, msg));
+                                }
+                            }
+                        }
+                    };
+                    if self.peek().kind == TokenKind::Colon {
+                        self.advance();
+                    }
+                    let Some(first) = keys.get(&key).copied() else {
+                        keys.insert(key.clone(), token);
+                        members.insert(key, self.parse_value()?);
+                        continue;
+                    };
+
+                    let msg = format!("Duplicate key \"{}\", first defined at {}:{}.", key, first.line, first.column);
+                    let value = match self.duplicate_keys {
+                        DuplicateKeys::Error => return Err(self.report_at(token, token.span, msg)),
+                        DuplicateKeys::Warn => {
+                            let warning = self.report_at(token, token.span, msg);
+                            self.warn(warning)?;
+                            self.parse_value()?
+                        }
+                        DuplicateKeys::LastWins => self.parse_value()?,
+                        // Still evaluated, so side effects happen as written.
+                        DuplicateKeys::FirstWins => {
+                            self.parse_value()?;
+                            continue;
+                        }
+                    };
+                    members.insert(key, value);
+                },
+                TokenKind::Spread => match self.parse_value()? {
+                    // Spread members override the ones before them and are overridden by the ones after.
+                    AsonValue::Object(spread) => members.extend(spread),
+                    other => {
+                        let msg = format!("Only an object can be spread into an object, got {}.", other.type_name());
+                        return Err(self.report_at(token, token.span, msg));
+                    }
                 },
                 TokenKind::Comma => continue,
                 _ => return Err(self.report(format!("Unexpected token: {}", self.peek().lexem)))
             }
         }
-
         Ok(AsonValue::Object(members))
     }
 
     fn parse_array(&mut self) -> ParserResult<AsonValue> {
+        self.nested(Self::array)
+    }
+
+    fn array(&mut self) -> ParserResult<AsonValue> {
         let mut elements = Vec::<AsonValue>::new();
-        while !self.is_at_end() {
+        let open = &self.tokens[self.current - 1];
+        loop {
+            if self.is_at_end() {
+                return Err(self.report_at(open, open.span, "Unclosed array, expected '['.".into()));
+            }
             match self.peek().kind {
                 TokenKind::CloseArray => {
                     _ = self.advance();
                     break;
                 },
                 TokenKind::Comma => _ = self.advance(),
+                TokenKind::Spread => {
+                    let token = self.advance();
+                    match self.parse_value()? {
+                        AsonValue::Array(spread) => elements.extend(spread),
+                        other => {
+                            let msg = format!("Only an array can be spread into an array, got {}.", other.type_name());
+                            return Err(self.report_at(token, token.span, msg));
+                        }
+                    }
+                }
                 _ => elements.push(self.parse_value()?),
             }
         }
-
-        Ok(AsonValue::Array(elements))
+        self.check_size(AsonValue::Array(elements))
     }
 
     fn parse_expr(&mut self) -> ParserResult<AsonValue> {
+        let open = &self.tokens[self.current - 1];
         let expr = self.parse_expr_s()?;
-        match expr.eval(&mut self.env) {
+        self.program.recompile(&expr);
+        match self.vm.run(&self.program, self.env) {
             Ok(v) => Ok(v),
-            Err(e) => {
-                let msg = match e {
-                    RuntimeError::NotEnoughArgument { given, expected } => format!("Not Enough Arguments given, got {} expected {}.", given, expected),
-                    RuntimeError::TooMuchArgument { given, expected } => format!("Too Much Arguments given, got {} expected {}.", given, expected),
-                    RuntimeError::UndefinedSymbol => "Undefined Symbol.".into(),
-                    RuntimeError::NotAFunction => "Not a function.".into(),
-                };
-                Err(self.report(msg))
-            }
+            Err(e) => Err(self.report_at(open, expr.span(), e.to_string())),
         }
     }
 
     fn parse_expr_s(&mut self) -> ParserResult<AsonExpr> {
+        self.nested(Self::expr_s)
+    }
+
+    fn expr_s(&mut self) -> ParserResult<AsonExpr> {
+        let open = self.tokens[self.current - 1].span;
         let mut params = Vec::new();
         while !self.is_at_end() {
             if self.peek().kind == TokenKind::CloseExpr {
                 break;
             }
             let tok = self.advance();
+            let span = tok.span;
             match tok.kind {
-                TokenKind::IntegerLiteral(v) => params.push(AsonExpr::Value(v.into())),
-                TokenKind::FloatLiteral(v) => params.push(AsonExpr::Value(v.into())),
-                TokenKind::StringLiteral(ref v) => params.push(AsonExpr::Value(v.clone().into())),
-                TokenKind::True => params.push(AsonExpr::Value(true.into())),
-                TokenKind::False => params.push(AsonExpr::Value(false.into())),
-                TokenKind::Null => params.push(AsonExpr::Value(AsonValue::Null)),
-                TokenKind::Symbol(ref v) => params.push(AsonExpr::Symbol(v.to_string())),
+                TokenKind::IntegerLiteral(v) => params.push(AsonExpr::Value(v.into(), span)),
+                TokenKind::FloatLiteral(v) => params.push(AsonExpr::Value(v.into(), span)),
+                TokenKind::StringLiteral(ref v) => params.push(AsonExpr::Value(v.clone().into(), span)),
+                TokenKind::True => params.push(AsonExpr::Value(true.into(), span)),
+                TokenKind::False => params.push(AsonExpr::Value(false.into(), span)),
+                TokenKind::Null => params.push(AsonExpr::Value(AsonValue::Null, span)),
+                TokenKind::Symbol(ref v) => params.push(AsonExpr::Symbol(v.to_string(), span)),
                 TokenKind::OpenExpr => params.push(self.parse_expr_s()?),
 
                 _ => return Err(self.report(
@@ -141,15 +280,16 @@ impl<'a> Parser<'a> {
                 )),
             }
         }
-        _ = self.consume(TokenKind::CloseExpr, "Expected closing expressios-s '('".into())?;
+        let close = self.consume(TokenKind::CloseExpr, "Expected closing expressios-s '('".into())?;
 
+        let span = open.to(close.span);
         if let Some(s) = params.pop() {
             match s {
-                AsonExpr::Symbol(s) => Ok(AsonExpr::ExprS(params, s)),
+                AsonExpr::Symbol(s, _) => Ok(AsonExpr::ExprS(params, s, span)),
                 _ => Err(self.report("help".into()))
             }
         } else {
-            Ok(AsonExpr::None)
+            Ok(AsonExpr::None(span))
         }
     }
 
@@ -176,17 +316,104 @@ impl<'a> Parser<'a> {
         Err(self.report(msg))
     }
 
+    /// Runs `parse` one level deeper, leaving the level again however it ends.
+    fn nested<T>(&mut self, parse: fn(&mut Self) -> ParserResult<T>) -> ParserResult<T> {
+        self.env.enter().map_err(|e| self.report(e.to_string()))?;
+        let result = parse(self);
+        self.env.leave();
+        result
+    }
+
+    fn check_size(&self, value: AsonValue) -> ParserResult<AsonValue> {
+        match self.env.check_size(&value) {
+            Ok(()) => Ok(value),
+            Err(e) => Err(self.report(e.to_string())),
+        }
+    }
+
+    fn warn(&mut self, warning: ParserError) -> ParserResult<()> {
+        if let Err(e) = self.env.io.write_stderr(&format!("Warning {}\n", warning)) {
+            return Err(self.report(e.to_string()));
+        }
+        self.env.warnings.push(warning);
+        Ok(())
+    }
+
     fn is_at_end(&self) -> bool {
         self.current >= self.tokens.len()
     }
 
     fn report(&self, msg: String) -> ParserError {
         let current = self.peek();
+        self.report_at(current, current.span, msg)
+    }
+
+    fn report_at(&self, token: &Token, span: Span, msg: String) -> ParserError {
         ParserError {
             msg,
             file: self.file.clone(),
-            line: current.line,
-            column: current.column,
+            line: token.line,
+            column: token.column,
+            span,
+        }
+    }
+}
+
+pub(crate) fn tokenize<'a>(s: &'a str, file: &str) -> ParserResult<TokenList<'a>> {
+    let mut lex = Lexer::new(s);
+    if let Err(msg) = lex.scan() {
+        let span = Span::new(lex.index(), lex.index());
+        return Err(ParserError::new(msg, file.into(), lex.line(), lex.column(), span));
+    }
+    Ok(lex.into_tokens())
+}
+
+/// Evaluates a sequence of top-level values one at a time, sharing the
+/// environment so `defvar` bindings carry over. It stops after the first error.
+#[derive(Debug)]
+pub struct Documents<'a> {
+    tokens: TokenList<'a>,
+    current: usize,
+    env: &'a mut Environment,
+    file: String,
+    path: Option<PathBuf>,
+    error: Option<ParserError>,
+}
+
+impl<'a> Documents<'a> {
+    /// `path`, when given, is where `include` and `import` resolve from.
+    pub fn new(s: &'a str, path: Option<&Path>, env: &'a mut Environment) -> Self {
+        let file = path.map_or("<string>".to_string(), |p| p.display().to_string());
+        let (tokens, error) = match tokenize(s, &file) {
+            Ok(tokens) => (tokens, None),
+            Err(e) => (TokenList::new(), Some(e)),
+        };
+        Documents { tokens, current: 0, env, file, path: path.map(Path::to_path_buf), error }
+    }
+}
+
+impl Iterator for Documents<'_> {
+    type Item = ParserResult<AsonValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+        if self.current >= self.tokens.len() {
+            return None;
+        }
+
+        self.env.reset_budget();
+        if let Some(path) = &self.path {
+            self.env.push_file(path);
+        }
+        let mut parser = Parser::new(&self.tokens, self.file.clone(), self.env);
+        parser.current = self.current;
+        let result = parser.parse_value();
+        self.current = if result.is_ok() { parser.current } else { self.tokens.len() };
+        if self.path.is_some() {
+            self.env.pop_file();
         }
+        Some(result)
     }
 }
@@ -0,0 +1,185 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::ast::{pointer_tokens, AsonNumber, AsonValue};
+
+#[derive(Debug)]
+pub struct PatchError {
+    msg: String,
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+pub type PatchResult<T> = Result<T, PatchError>;
+
+fn error<T>(msg: String) -> PatchResult<T> {
+    Err(PatchError { msg })
+}
+
+/// Applies an RFC 7386 merge patch: members of `patch` replace those of
+/// `target`, `null` members are removed, and nested objects are merged.
+pub fn merge_patch(target: &mut AsonValue, patch: &AsonValue) {
+    let AsonValue::Object(members) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = AsonValue::Object(HashMap::new());
+    }
+    let target = target.as_object_mut().unwrap();
+    for (key, value) in members {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(AsonValue::Null), value);
+        }
+    }
+}
+
+/// Applies an RFC 6902 JSON Patch, an array of `add`, `remove`, `replace`,
+/// `move`, `copy` and `test` operations. Either every operation is applied
+/// or `target` is left untouched.
+pub fn apply_patch(target: &mut AsonValue, patch: &AsonValue) -> PatchResult<()> {
+    let Some(operations) = patch.as_array() else {
+        return error("a JSON patch must be an array of operations".into());
+    };
+    let mut result = target.clone();
+    for (i, operation) in operations.iter().enumerate() {
+        apply_operation(&mut result, operation).map_err(|e| PatchError { msg: format!("operation {}: {}", i, e) })?;
+    }
+    *target = result;
+    Ok(())
+}
+
+fn apply_operation(target: &mut AsonValue, operation: &AsonValue) -> PatchResult<()> {
+    let member = |name: &str| match operation.get(name) {
+        Some(v) => Ok(v),
+        None => error(format!("missing \"{}\"", name)),
+    };
+    let pointer = |name: &str| match member(name)?.as_str() {
+        Some(p) => Ok(p),
+        None => error(format!("\"{}\" must be a string", name)),
+    };
+
+    match member("op")?.as_str() {
+        Some("add") => add(target, pointer("path")?, member("value")?.clone()),
+        Some("remove") => remove(target, pointer("path")?).map(|_| ()),
+        Some("replace") => {
+            let path = pointer("path")?;
+            match target.pointer_mut(path) {
+                Some(v) => {
+                    *v = member("value")?.clone();
+                    Ok(())
+                }
+                None => error(format!("\"{}\" does not exist", path)),
+            }
+        }
+        Some("move") => {
+            let (from, path) = (pointer("from")?, pointer("path")?);
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                return error(format!("cannot move \"{}\" into itself", from));
+            }
+            let value = remove(target, from)?;
+            add(target, path, value)
+        }
+        Some("copy") => {
+            let from = pointer("from")?;
+            match target.pointer(from) {
+                Some(v) => {
+                    let value = v.clone();
+                    add(target, pointer("path")?, value)
+                }
+                None => error(format!("\"{}\" does not exist", from)),
+            }
+        }
+        Some("test") => {
+            let (path, value) = (pointer("path")?, member("value")?);
+            if target.pointer(path).is_some_and(|v| equal(v, value)) {
+                Ok(())
+            } else {
+                error(format!("test failed at \"{}\"", path))
+            }
+        }
+        Some(op) => error(format!("unknown operation \"{}\"", op)),
+        None => error("\"op\" must be a string".into()),
+    }
+}
+
+/// Equality as RFC 6902 defines it for `test`, where numbers are compared by
+/// value, so `1` equals `1.0`.
+fn equal(a: &AsonValue, b: &AsonValue) -> bool {
+    match (a, b) {
+        (AsonValue::Number(AsonNumber::Integer(x)), AsonValue::Number(AsonNumber::Integer(y))) => x == y,
+        (AsonValue::Number(_), AsonValue::Number(_)) => a.as_f64() == b.as_f64(),
+        (AsonValue::Array(x), AsonValue::Array(y)) => x.len() == y.len() && x.iter().zip(y).all(|(x, y)| equal(x, y)),
+        (AsonValue::Object(x), AsonValue::Object(y)) => {
+            x.len() == y.len() && x.iter().all(|(k, v)| y.get(k).is_some_and(|w| equal(v, w)))
+        }
+        _ => a == b,
+    }
+}
+
+/// Splits a pointer into its parent pointer and last reference token.
+fn split_pointer(pointer: &str) -> PatchResult<(&str, String)> {
+    match pointer.rfind('/') {
+        Some(i) => {
+            let last = pointer_tokens(&pointer[i..]).and_then(|mut t| t.pop()).unwrap_or_default();
+            Ok((&pointer[..i], last))
+        }
+        None => error(format!("invalid pointer \"{}\"", pointer)),
+    }
+}
+
+fn add(target: &mut AsonValue, pointer: &str, value: AsonValue) -> PatchResult<()> {
+    if pointer.is_empty() {
+        *target = value;
+        return Ok(());
+    }
+    let (parent, last) = split_pointer(pointer)?;
+    match target.pointer_mut(parent) {
+        Some(AsonValue::Object(m)) => {
+            m.insert(last, value);
+            Ok(())
+        }
+        Some(AsonValue::Array(a)) => {
+            let index = if last == "-" { a.len() } else { parse_index(&last)? };
+            if index > a.len() {
+                return error(format!("index {} is out of range", index));
+            }
+            a.insert(index, value);
+            Ok(())
+        }
+        Some(_) => error(format!("\"{}\" is not a container", parent)),
+        None => error(format!("\"{}\" does not exist", parent)),
+    }
+}
+
+fn remove(target: &mut AsonValue, pointer: &str) -> PatchResult<AsonValue> {
+    if pointer.is_empty() {
+        return Ok(std::mem::replace(target, AsonValue::Null));
+    }
+    let (parent, last) = split_pointer(pointer)?;
+    let removed = match target.pointer_mut(parent) {
+        Some(AsonValue::Object(m)) => m.remove(&last),
+        Some(AsonValue::Array(a)) => {
+            let index = parse_index(&last)?;
+            (index < a.len()).then(|| a.remove(index))
+        }
+        _ => None,
+    };
+    match removed {
+        Some(v) => Ok(v),
+        None => error(format!("\"{}\" does not exist", pointer)),
+    }
+}
+
+fn parse_index(token: &str) -> PatchResult<usize> {
+    match token.parse::<usize>() {
+        Ok(i) if token == "0" || !token.starts_with('0') => Ok(i),
+        _ => error(format!("\"{}\" is not an array index", token)),
+    }
+}
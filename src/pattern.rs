@@ -0,0 +1,394 @@
+/// A small regular expression matcher covering what schema `pattern`s use
+/// in practice: literals, `.`, classes (`[a-z]`, `[^0-9]`, `\d`, `\w`,
+/// `\s`), anchors, groups, alternation and the `*`, `+`, `?`, `{n,m}`
+/// quantifiers. Patterns are compiled to a program run as a Pike VM, which
+/// follows every possible match at once, so matching takes time linear in the
+/// input and a budget of steps.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    program: Vec<Inst>,
+}
+
+/// Instructions past this count make a pattern too large, so that counted
+/// repetitions like `(a{100}){100}` can't blow up.
+const MAX_PROGRAM: usize = 10_000;
+
+/// Groups nested deeper than this are rejected.
+const MAX_NESTING: usize = 256;
+
+/// Threads stepped through before a match is given up.
+pub const MAX_STEPS: u64 = 10_000_000;
+
+#[derive(Debug, Clone)]
+enum ClassItem {
+    Char(char),
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Char(char),
+    Any,
+    Class(Vec<ClassItem>, bool),
+    Start,
+    End,
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat(Box<Node>, usize, Option<usize>),
+}
+
+#[derive(Debug, Clone)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Vec<ClassItem>, bool),
+    Start,
+    End,
+    /// Continues at both targets.
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+impl Pattern {
+    pub fn new(source: &str) -> Result<Pattern, String> {
+        let mut parser = PatternParser { chars: source.chars().collect(), current: 0, depth: 0 };
+        let root = parser.parse_alt()?;
+        if parser.current < parser.chars.len() {
+            return Err(format!("Unexpected '{}' in pattern.", parser.chars[parser.current]));
+        }
+        let mut program = Vec::new();
+        compile(&root, &mut program)?;
+        program.push(Inst::Match);
+        Ok(Pattern { program })
+    }
+
+    /// Whether the pattern matches anywhere in `s`, failing once matching
+    /// takes more than `MAX_STEPS` steps.
+    pub fn is_match(&self, s: &str) -> Result<bool, String> {
+        self.is_match_within(s, MAX_STEPS)
+    }
+
+    /// Like `is_match`, giving up after `max_steps` steps.
+    pub fn is_match_within(&self, s: &str, max_steps: u64) -> Result<bool, String> {
+        let chars: Vec<char> = s.chars().collect();
+        let mut vm = PikeVm { program: &self.program, seen: vec![usize::MAX; self.program.len()], steps: 0, max_steps };
+        let mut current = Vec::new();
+        let mut next = Vec::new();
+        for i in 0..=chars.len() {
+            // A thread starts at every position, so the match can be anywhere.
+            if vm.add(&mut current, 0, i, chars.len())? {
+                return Ok(true);
+            }
+            let Some(&c) = chars.get(i) else { break };
+            for &pc in &current {
+                let matches = match &self.program[pc] {
+                    Inst::Char(x) => c == *x,
+                    Inst::Any => c != '\n',
+                    Inst::Class(items, negated) => items.iter().any(|item| class_matches(item, c)) != *negated,
+                    _ => false,
+                };
+                if matches && vm.add(&mut next, pc + 1, i + 1, chars.len())? {
+                    return Ok(true);
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+            next.clear();
+        }
+        Ok(false)
+    }
+}
+
+fn compile(node: &Node, program: &mut Vec<Inst>) -> Result<(), String> {
+    if program.len() > MAX_PROGRAM {
+        return Err("Pattern is too large.".into());
+    }
+    match node {
+        Node::Char(c) => program.push(Inst::Char(*c)),
+        Node::Any => program.push(Inst::Any),
+        Node::Class(items, negated) => program.push(Inst::Class(items.clone(), *negated)),
+        Node::Start => program.push(Inst::Start),
+        Node::End => program.push(Inst::End),
+        Node::Concat(nodes) => {
+            for node in nodes {
+                compile(node, program)?;
+            }
+        }
+        Node::Alt(alternatives) => {
+            let mut jumps = Vec::new();
+            for (i, alternative) in alternatives.iter().enumerate() {
+                if i + 1 == alternatives.len() {
+                    compile(alternative, program)?;
+                    break;
+                }
+                let split = program.len();
+                program.push(Inst::Split(split + 1, 0));
+                compile(alternative, program)?;
+                jumps.push(program.len());
+                program.push(Inst::Jump(0));
+                program[split] = Inst::Split(split + 1, program.len());
+            }
+            let end = program.len();
+            for jump in jumps {
+                program[jump] = Inst::Jump(end);
+            }
+        }
+        Node::Repeat(inner, min, max) => {
+            for _ in 0..*min {
+                compile(inner, program)?;
+            }
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(inner, program)?;
+                    program.push(Inst::Jump(split));
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(0, 0));
+                        compile(inner, program)?;
+                    }
+                    let end = program.len();
+                    for split in splits {
+                        program[split] = Inst::Split(split + 1, end);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+struct PikeVm<'p> {
+    program: &'p [Inst],
+    /// The position each instruction was last added at, so a thread is only
+    /// followed once per position and empty loops end.
+    seen: Vec<usize>,
+    steps: u64,
+    max_steps: u64,
+}
+
+impl PikeVm<'_> {
+    /// Adds the thread at `pc` to `threads`, following jumps, splits and
+    /// anchors up to the instructions that consume a character. Returns
+    /// whether one of them reached `Match`.
+    fn add(&mut self, threads: &mut Vec<usize>, pc: usize, i: usize, len: usize) -> Result<bool, String> {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if self.seen[pc] == i {
+                continue;
+            }
+            self.seen[pc] = i;
+            self.steps += 1;
+            if self.steps > self.max_steps {
+                return Err(format!("matching took more than {} steps", self.max_steps));
+            }
+            match self.program[pc] {
+                Inst::Match => return Ok(true),
+                Inst::Jump(target) => stack.push(target),
+                Inst::Split(a, b) => {
+                    stack.push(b);
+                    stack.push(a);
+                }
+                Inst::Start if i == 0 => stack.push(pc + 1),
+                Inst::End if i == len => stack.push(pc + 1),
+                Inst::Start | Inst::End => {}
+                _ => threads.push(pc),
+            }
+        }
+        Ok(false)
+    }
+}
+
+fn class_matches(item: &ClassItem, c: char) -> bool {
+    match *item {
+        ClassItem::Char(x) => c == x,
+        ClassItem::Range(a, b) => a <= c && c <= b,
+        ClassItem::Digit(negated) => c.is_ascii_digit() != negated,
+        ClassItem::Word(negated) => (c.is_alphanumeric() || c == '_') != negated,
+        ClassItem::Space(negated) => c.is_whitespace() != negated,
+    }
+}
+
+struct PatternParser {
+    chars: Vec<char>,
+    current: usize,
+    depth: usize,
+}
+
+impl PatternParser {
+    fn parse_alt(&mut self) -> Result<Node, String> {
+        let mut alternatives = vec![self.parse_concat()?];
+        while self.peek() == Some('|') {
+            self.current += 1;
+            alternatives.push(self.parse_concat()?);
+        }
+        if alternatives.len() == 1 {
+            Ok(alternatives.pop().unwrap())
+        } else {
+            Ok(Node::Alt(alternatives))
+        }
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, String> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.parse_atom()?;
+            nodes.push(self.parse_quantifier(atom)?);
+        }
+        Ok(Node::Concat(nodes))
+    }
+
+    fn parse_quantifier(&mut self, atom: Node) -> Result<Node, String> {
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => return self.parse_braces(atom),
+            _ => return Ok(atom),
+        };
+        self.current += 1;
+        // Lazy quantifiers match the same strings, so `?` after one is ignored.
+        if self.peek() == Some('?') {
+            self.current += 1;
+        }
+        Ok(Node::Repeat(Box::new(atom), min, max))
+    }
+
+    fn parse_braces(&mut self, atom: Node) -> Result<Node, String> {
+        let start = self.current;
+        self.current += 1;
+        let min = self.parse_count();
+        let max = if self.peek() == Some(',') {
+            self.current += 1;
+            self.parse_count()
+        } else {
+            min
+        };
+        match (min, self.peek()) {
+            (Some(min), Some('}')) => {
+                self.current += 1;
+                Ok(Node::Repeat(Box::new(atom), min, max))
+            }
+            _ => {
+                // Not a quantifier, treat the brace as a literal.
+                self.current = start;
+                let brace = self.parse_atom()?;
+                Ok(Node::Concat(vec![atom, brace]))
+            }
+        }
+    }
+
+    fn parse_count(&mut self) -> Option<usize> {
+        let start = self.current;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.current += 1;
+        }
+        self.chars[start..self.current].iter().collect::<String>().parse().ok()
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, String> {
+        let c = self.peek().ok_or("Unexpected end of pattern.")?;
+        self.current += 1;
+        match c {
+            '.' => Ok(Node::Any),
+            '^' => Ok(Node::Start),
+            '$' => Ok(Node::End),
+            '(' => {
+                if self.chars[self.current..].starts_with(&['?', ':']) {
+                    self.current += 2;
+                }
+                if self.depth >= MAX_NESTING {
+                    return Err("Groups are nested too deeply in pattern.".into());
+                }
+                self.depth += 1;
+                let inner = self.parse_alt()?;
+                self.depth -= 1;
+                if self.peek() != Some(')') {
+                    return Err("Unclosed group in pattern.".into());
+                }
+                self.current += 1;
+                Ok(inner)
+            }
+            '[' => self.parse_class(),
+            '\\' => match self.parse_escape()? {
+                ClassItem::Char(c) => Ok(Node::Char(c)),
+                item => Ok(Node::Class(vec![item], false)),
+            },
+            '*' | '+' | '?' => Err(format!("Nothing to repeat before '{}'.", c)),
+            c => Ok(Node::Char(c)),
+        }
+    }
+
+    fn parse_class(&mut self) -> Result<Node, String> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.current += 1;
+        }
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let c = self.peek().ok_or("Unclosed character class in pattern.")?;
+            self.current += 1;
+            let item = match c {
+                ']' if !first => break,
+                '\\' => self.parse_escape()?,
+                c => ClassItem::Char(c),
+            };
+            first = false;
+            match item {
+                ClassItem::Char(start) if self.peek() == Some('-') && self.chars.get(self.current + 1).is_some_and(|c| *c != ']') => {
+                    self.current += 1;
+                    let end = match self.peek() {
+                        Some('\\') => {
+                            self.current += 1;
+                            match self.parse_escape()? {
+                                ClassItem::Char(c) => c,
+                                _ => return Err("Invalid range in character class.".into()),
+                            }
+                        }
+                        Some(c) => {
+                            self.current += 1;
+                            c
+                        }
+                        None => return Err("Unclosed character class in pattern.".into()),
+                    };
+                    items.push(ClassItem::Range(start, end));
+                }
+                item => items.push(item),
+            }
+        }
+        Ok(Node::Class(items, negated))
+    }
+
+    fn parse_escape(&mut self) -> Result<ClassItem, String> {
+        let c = self.peek().ok_or("Escape at end of pattern.")?;
+        self.current += 1;
+        Ok(match c {
+            'd' => ClassItem::Digit(false),
+            'D' => ClassItem::Digit(true),
+            'w' => ClassItem::Word(false),
+            'W' => ClassItem::Word(true),
+            's' => ClassItem::Space(false),
+            'S' => ClassItem::Space(true),
+            'n' => ClassItem::Char('\n'),
+            'r' => ClassItem::Char('\r'),
+            't' => ClassItem::Char('\t'),
+            c => ClassItem::Char(c),
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+}
@@ -0,0 +1,440 @@
+use core::fmt;
+use std::cmp::Ordering;
+
+use crate::ast::AsonValue;
+
+#[derive(Debug)]
+pub struct QueryError {
+    msg: String,
+    position: usize,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at position {}: {}", self.position, self.msg)
+    }
+}
+
+pub type QueryResult<T> = Result<T, QueryError>;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CompareOp {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+/// `[?(@.path op literal)]`, or `[?(@.path)]` to test that the path exists.
+#[derive(Debug)]
+pub struct Filter {
+    pub path: Vec<String>,
+    pub comparison: Option<(CompareOp, AsonValue)>,
+}
+
+#[derive(Debug)]
+pub enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, i64),
+    Filter(Filter),
+}
+
+#[derive(Debug)]
+pub struct Segment {
+    pub recursive: bool,
+    pub selector: Selector,
+}
+
+/// A compiled path expression in a JSONPath-like syntax:
+///
+/// - `$.name`, `$['name']` select a member, `$[0]`, `$[-1]` an element
+/// - `.*`, `[*]` select every member or element
+/// - `..name` searches every descendant
+/// - `[start:end:step]` slices an array
+/// - `[?(@.port >= 8000)]` keeps the children matching a comparison
+#[derive(Debug)]
+pub struct Query {
+    segments: Vec<Segment>,
+}
+
+impl Query {
+    pub fn parse(expr: &str) -> QueryResult<Query> {
+        QueryParser { chars: expr.chars().collect(), current: 0 }.parse()
+    }
+
+    pub fn select<'v>(&self, root: &'v AsonValue) -> Vec<&'v AsonValue> {
+        let mut nodes = vec![root];
+        for segment in &self.segments {
+            let mut next = Vec::new();
+            for node in nodes {
+                if segment.recursive {
+                    let mut descendants = Vec::new();
+                    collect_descendants(node, &mut descendants);
+                    for d in descendants {
+                        apply_selector(&segment.selector, d, &mut next);
+                    }
+                } else {
+                    apply_selector(&segment.selector, node, &mut next);
+                }
+            }
+            nodes = next;
+        }
+        nodes
+    }
+}
+
+/// Runs the query `expr` against `value`.
+pub fn query<'v>(value: &'v AsonValue, expr: &str) -> QueryResult<Vec<&'v AsonValue>> {
+    Ok(Query::parse(expr)?.select(value))
+}
+
+/// Extends a path in the query syntax, e.g. `$.servers[0].port`.
+pub fn member_path(path: &str, key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+        format!("{}.{}", path, key)
+    } else {
+        format!("{}['{}']", path, key.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+}
+
+pub fn index_path(path: &str, index: usize) -> String {
+    format!("{}[{}]", path, index)
+}
+
+fn collect_descendants<'v>(value: &'v AsonValue, out: &mut Vec<&'v AsonValue>) {
+    out.push(value);
+    match value {
+        AsonValue::Object(m) => m.values().for_each(|v| collect_descendants(v, out)),
+        AsonValue::Array(a) => a.iter().for_each(|v| collect_descendants(v, out)),
+        _ => {}
+    }
+}
+
+fn children(value: &AsonValue) -> Vec<&AsonValue> {
+    match value {
+        AsonValue::Object(m) => m.values().collect(),
+        AsonValue::Array(a) => a.iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn apply_selector<'v>(selector: &Selector, value: &'v AsonValue, out: &mut Vec<&'v AsonValue>) {
+    match selector {
+        Selector::Name(name) => out.extend(value.get(name)),
+        Selector::Wildcard => out.extend(children(value)),
+        Selector::Index(i) => {
+            if let AsonValue::Array(a) = value {
+                let i = if *i < 0 { a.len() as i64 + i } else { *i };
+                if i >= 0 {
+                    out.extend(a.get(i as usize));
+                }
+            }
+        }
+        Selector::Slice(start, end, step) => {
+            if let AsonValue::Array(a) = value {
+                for i in slice_indices(a.len() as i64, *start, *end, *step) {
+                    out.push(&a[i]);
+                }
+            }
+        }
+        Selector::Filter(filter) => {
+            out.extend(children(value).into_iter().filter(|c| matches_filter(filter, c)));
+        }
+    }
+}
+
+fn slice_indices(len: i64, start: Option<i64>, end: Option<i64>, step: i64) -> Vec<usize> {
+    let normalize = |i: i64| if i < 0 { (len + i).max(0) } else { i.min(len) };
+    let mut indices = Vec::new();
+    if step > 0 {
+        let mut i = start.map_or(0, normalize);
+        let end = end.map_or(len, normalize);
+        while i < end {
+            indices.push(i as usize);
+            match i.checked_add(step) {
+                Some(next) => i = next,
+                None => break,
+            }
+        }
+    } else if step < 0 {
+        let mut i = start.map_or(len - 1, |s| normalize(s).min(len - 1));
+        let end = end.map_or(-1, normalize);
+        while i > end {
+            indices.push(i as usize);
+            match i.checked_add(step) {
+                Some(next) => i = next,
+                None => break,
+            }
+        }
+    }
+    indices
+}
+
+fn matches_filter(filter: &Filter, value: &AsonValue) -> bool {
+    let mut target = value;
+    for name in &filter.path {
+        match target.get(name) {
+            Some(v) => target = v,
+            None => return false,
+        }
+    }
+    match &filter.comparison {
+        None => true,
+        Some((op, literal)) => compare(target, *op, literal),
+    }
+}
+
+fn compare(lhs: &AsonValue, op: CompareOp, rhs: &AsonValue) -> bool {
+    let ordering = match (lhs, rhs) {
+        (AsonValue::Number(_), AsonValue::Number(_)) => lhs.as_f64().partial_cmp(&rhs.as_f64()),
+        (AsonValue::String(a), AsonValue::String(b)) => Some(a.cmp(b)),
+        _ if lhs == rhs => Some(Ordering::Equal),
+        _ => None,
+    };
+    match op {
+        CompareOp::Equal => ordering == Some(Ordering::Equal),
+        CompareOp::NotEqual => ordering != Some(Ordering::Equal),
+        CompareOp::Less => ordering == Some(Ordering::Less),
+        CompareOp::LessEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        CompareOp::Greater => ordering == Some(Ordering::Greater),
+        CompareOp::GreaterEqual => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+    }
+}
+
+struct QueryParser {
+    chars: Vec<char>,
+    current: usize,
+}
+
+impl QueryParser {
+    fn parse(mut self) -> QueryResult<Query> {
+        let mut segments = Vec::new();
+        self.eat('$');
+        while let Some(c) = self.peek() {
+            match c {
+                '.' => {
+                    self.current += 1;
+                    let recursive = self.eat('.');
+                    let selector = match self.peek() {
+                        Some('*') => {
+                            self.current += 1;
+                            Selector::Wildcard
+                        }
+                        Some('[') if recursive => self.parse_bracket()?,
+                        _ => Selector::Name(self.parse_name()?),
+                    };
+                    segments.push(Segment { recursive, selector });
+                }
+                '[' => segments.push(Segment { recursive: false, selector: self.parse_bracket()? }),
+                c => return Err(self.error(format!("Unexpected character '{}'.", c))),
+            }
+        }
+        Ok(Query { segments })
+    }
+
+    fn parse_bracket(&mut self) -> QueryResult<Selector> {
+        self.expect('[')?;
+        self.skip_whitespace();
+        let selector = match self.peek() {
+            Some('*') => {
+                self.current += 1;
+                Selector::Wildcard
+            }
+            Some('\'' | '"') => Selector::Name(self.parse_string()?),
+            Some('?') => {
+                self.current += 1;
+                Selector::Filter(self.parse_filter()?)
+            }
+            _ => self.parse_index_or_slice()?,
+        };
+        self.skip_whitespace();
+        self.expect(']')?;
+        Ok(selector)
+    }
+
+    fn parse_index_or_slice(&mut self) -> QueryResult<Selector> {
+        let start = self.parse_optional_int()?;
+        if !self.eat(':') {
+            return match start {
+                Some(i) => Ok(Selector::Index(i)),
+                None => Err(self.error("Expected an index, a slice, '*', a name or a filter.".into())),
+            };
+        }
+        let end = self.parse_optional_int()?;
+        let step = if self.eat(':') { self.parse_optional_int()?.unwrap_or(1) } else { 1 };
+        if step == 0 {
+            return Err(self.error("Slice step cannot be zero.".into()));
+        }
+        Ok(Selector::Slice(start, end, step))
+    }
+
+    fn parse_filter(&mut self) -> QueryResult<Filter> {
+        self.expect('(')?;
+        self.skip_whitespace();
+        self.expect('@')?;
+        let mut path = Vec::new();
+        while self.eat('.') {
+            path.push(self.parse_name()?);
+        }
+        self.skip_whitespace();
+        let op = if self.eat_str("==") {
+            Some(CompareOp::Equal)
+        } else if self.eat_str("!=") {
+            Some(CompareOp::NotEqual)
+        } else if self.eat_str("<=") {
+            Some(CompareOp::LessEqual)
+        } else if self.eat_str(">=") {
+            Some(CompareOp::GreaterEqual)
+        } else if self.eat('<') {
+            Some(CompareOp::Less)
+        } else if self.eat('>') {
+            Some(CompareOp::Greater)
+        } else {
+            None
+        };
+        let comparison = match op {
+            Some(op) => {
+                self.skip_whitespace();
+                Some((op, self.parse_literal()?))
+            }
+            None => None,
+        };
+        self.skip_whitespace();
+        self.expect(')')?;
+        Ok(Filter { path, comparison })
+    }
+
+    fn parse_literal(&mut self) -> QueryResult<AsonValue> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(AsonValue::String(self.parse_string()?)),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.current;
+                while let Some(c) = self.peek() {
+                    if !(c == '-' || c == '.' || c.is_ascii_digit()) {
+                        break;
+                    }
+                    self.current += 1;
+                }
+                let lexem: String = self.chars[start..self.current].iter().collect();
+                if let Ok(i) = lexem.parse::<i64>() {
+                    Ok(i.into())
+                } else if let Ok(f) = lexem.parse::<f64>() {
+                    Ok(f.into())
+                } else {
+                    Err(self.error(format!("Invalid number: {}", lexem)))
+                }
+            }
+            _ => match self.parse_name()?.as_str() {
+                "true" => Ok(AsonValue::Boolean(true)),
+                "false" => Ok(AsonValue::Boolean(false)),
+                "null" => Ok(AsonValue::Null),
+                word => Err(self.error(format!("Unexpected word: {}", word))),
+            },
+        }
+    }
+
+    fn parse_name(&mut self) -> QueryResult<String> {
+        let start = self.current;
+        while let Some(c) = self.peek() {
+            if matches!(c, '.' | '[' | ']' | '(' | ')' | '=' | '!' | '<' | '>') || c.is_whitespace() {
+                break;
+            }
+            self.current += 1;
+        }
+        if start == self.current {
+            return Err(self.error("Expected a member name.".into()));
+        }
+        Ok(self.chars[start..self.current].iter().collect())
+    }
+
+    fn parse_string(&mut self) -> QueryResult<String> {
+        let quote = self.peek().unwrap();
+        self.current += 1;
+        let mut content = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("Unterminated string.".into())),
+                Some('\\') => {
+                    self.current += 1;
+                    if let Some(c) = self.peek() {
+                        content.push(c);
+                        self.current += 1;
+                    }
+                }
+                Some(c) if c == quote => {
+                    self.current += 1;
+                    break;
+                }
+                Some(c) => {
+                    content.push(c);
+                    self.current += 1;
+                }
+            }
+        }
+        Ok(content)
+    }
+
+    fn parse_optional_int(&mut self) -> QueryResult<Option<i64>> {
+        self.skip_whitespace();
+        let start = self.current;
+        self.eat('-');
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.current += 1;
+        }
+        self.skip_whitespace();
+        let lexem: String = self.chars[start..self.current].iter().collect();
+        let lexem = lexem.trim();
+        if lexem.is_empty() {
+            return Ok(None);
+        }
+        lexem
+            .parse::<i64>()
+            .map(Some)
+            .map_err(|_| self.error(format!("Invalid integer: {}", lexem)))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.current += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        let len = s.chars().count();
+        if self.chars.len() >= self.current + len && self.chars[self.current..self.current + len].iter().copied().eq(s.chars()) {
+            self.current += len;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> QueryResult<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected '{}'.", c)))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.current += 1;
+        }
+    }
+
+    fn error(&self, msg: String) -> QueryError {
+        QueryError { msg, position: self.current }
+    }
+}
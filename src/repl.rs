@@ -0,0 +1,131 @@
+use std::io;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use crate::ast::AsonValue;
+use crate::environment::Environment;
+
+const HELP: &str = ":env          list the defined symbols
+:load <file>  evaluate a file, keeping its definitions
+:reset        forget every definition
+:help         show this message
+:quit         leave the repl";
+
+/// Counts how many `}`, `]` and `)` are still waiting for their closing
+/// `{`, `[` or `(`, skipping strings and comments. An unterminated raw
+/// string or block comment counts as one open bracket.
+pub fn open_brackets(source: &str) -> i64 {
+    let mut depth = 0;
+    let mut chars = source.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '}' | ']' | ')' => depth += 1,
+            '{' | '[' | '(' => depth -= 1,
+            '"' if chars.as_str().starts_with("\"\"") => {
+                let body = chars.as_str().trim_start_matches('"');
+                let fence = "\"".repeat(chars.as_str().len() - body.len() + 1);
+                match body.find(&fence) {
+                    Some(end) => chars = body[end + fence.len()..].trim_start_matches('"').chars(),
+                    None => return depth + 1,
+                }
+            }
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => _ = chars.next(),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '\\' if chars.as_str().starts_with('*') => match chars.as_str()[1..].find("*\\") {
+                Some(end) => chars = chars.as_str()[end + 3..].chars(),
+                None => return depth + 1,
+            },
+            '\\' if chars.as_str().starts_with('\\') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    depth
+}
+
+/// Reads values and expression-s from `input` and evaluates each against
+/// `env`, so `defvar` bindings carry over from one entry to the next. An
+/// entry may hold several values.
+pub fn run(env: &mut Environment, input: impl BufRead, output: &mut impl Write) -> io::Result<()> {
+    let mut pending = String::new();
+    write!(output, "ason> ")?;
+    output.flush()?;
+
+    for line in input.lines() {
+        let line = line?;
+        if pending.is_empty() && line.trim_start().starts_with(':') {
+            if !command(env, line.trim(), output)? {
+                return Ok(());
+            }
+        } else {
+            pending.push_str(&line);
+            pending.push('\n');
+            if open_brackets(&pending) > 0 {
+                write!(output, "...> ")?;
+                output.flush()?;
+                continue;
+            }
+            let blank = pending.lines().all(|l| l.trim().is_empty() || l.trim_start().starts_with("\\\\"));
+            if !blank {
+                for document in AsonValue::documents_in(&pending, env) {
+                    match document {
+                        Ok(v) => writeln!(output, "{}", v.to_pretty_json(2))?,
+                        Err(e) => writeln!(output, "Error {}", e)?,
+                    }
+                }
+            }
+            pending.clear();
+        }
+        write!(output, "ason> ")?;
+        output.flush()?;
+    }
+    writeln!(output)
+}
+
+/// Runs a `:` meta-command, returning false when the repl should stop.
+fn command(env: &mut Environment, line: &str, output: &mut impl Write) -> io::Result<bool> {
+    let (name, argument) = match line.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (line, ""),
+    };
+    match name {
+        ":env" => {
+            let mut names: Vec<&String> = env.symbols.keys().collect();
+            names.sort();
+            for name in names {
+                match &env.symbols[name] {
+                    AsonValue::Function(_) => writeln!(output, "{}: <function>", name)?,
+                    value => writeln!(output, "{}: {}", name, value.to_json())?,
+                }
+            }
+        }
+        ":load" if !argument.is_empty() => {
+            let path = Path::new(argument);
+            match env.read_to_string(path) {
+                Ok(source) => match AsonValue::from_ason_file_in(&source, path, env) {
+                    Ok(v) => writeln!(output, "{}", v.to_pretty_json(2))?,
+                    Err(e) => writeln!(output, "Error {}", e)?,
+                },
+                Err(e) => writeln!(output, "Error {}", e)?,
+            }
+        }
+        ":load" => writeln!(output, "Error :load expects a file")?,
+        ":reset" => env.reset(),
+        ":help" => writeln!(output, "{}", HELP)?,
+        ":quit" | ":q" => return Ok(false),
+        _ => writeln!(output, "Error unknown command '{}', try :help", name)?,
+    }
+    Ok(true)
+}
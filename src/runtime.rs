@@ -1,4 +1,7 @@
+use core::fmt;
+
 use crate::{ast::AsonValue, environment::Environment};
+use crate::limits::LimitKind;
 
 #[allow(dead_code)]
 #[derive(Debug, PartialEq, Clone)]
@@ -6,26 +9,63 @@ pub enum RuntimeError {
     NotEnoughArgument { given: u16, expected: u16 },
     TooMuchArgument { given: u16, expected: u16 },
     UndefinedSymbol,
-    NotAFunction
+    NotAFunction,
+    LimitExceeded { kind: LimitKind, max: u64 },
+    Io(String),
+    IncludeCycle(String),
+    IncludeFailed(String),
+    EnvVarNotSet(String),
+    InvalidArgument(String),
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeError::NotEnoughArgument { given, expected } => write!(f, "Not Enough Arguments given, got {} expected {}.", given, expected),
+            RuntimeError::TooMuchArgument { given, expected } => write!(f, "Too Much Arguments given, got {} expected {}.", given, expected),
+            RuntimeError::UndefinedSymbol => write!(f, "Undefined Symbol."),
+            RuntimeError::NotAFunction => write!(f, "Not a function."),
+            RuntimeError::LimitExceeded { kind, max } => match kind {
+                LimitKind::Depth => write!(f, "Maximum nesting depth of {} exceeded.", max),
+                LimitKind::Steps => write!(f, "Evaluation step budget of {} exhausted.", max),
+                LimitKind::StringSize => write!(f, "String longer than {} bytes.", max),
+                LimitKind::ArraySize => write!(f, "Array longer than {} elements.", max),
+                LimitKind::FileReadBytes => write!(f, "File larger than {} bytes.", max),
+                LimitKind::Timeout => write!(f, "Evaluation took longer than {}ms.", max),
+            },
+            RuntimeError::Io(msg) => write!(f, "IO error: {}", msg),
+            RuntimeError::IncludeCycle(path) => write!(f, "Include cycle: '{}' is already being evaluated.", path),
+            RuntimeError::IncludeFailed(err) => write!(f, "In included file {}", err),
+            RuntimeError::EnvVarNotSet(name) => write!(f, "Environment variable '{}' is not set.", name),
+            RuntimeError::InvalidArgument(msg) => write!(f, "{}", msg),
+        }
+    }
 }
 
-pub type Callback = fn(&[AsonValue], &mut Environment) -> AsonValue;
+pub type Callback = fn(&[AsonValue], &mut Environment) -> Result<AsonValue, RuntimeError>;
 
 #[allow(dead_code)]
 #[derive(Debug, PartialEq, Clone)]
 pub enum AsonExpectedArgs {
     AtLeast(u16),
     Exact(u16),
+    Between(u16, u16),
     None
 }
 
 #[allow(dead_code)]
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone)]
 pub struct AsonFunction {
     pub fun: Callback,
     pub expected_args: AsonExpectedArgs,
 }
 
+impl PartialEq for AsonFunction {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::fn_addr_eq(self.fun, other.fun) && self.expected_args == other.expected_args
+    }
+}
+
 impl AsonFunction {
     pub fn new(fun: Callback, expected_args: AsonExpectedArgs) -> Self {
         Self {
@@ -49,11 +89,21 @@ impl AsonFunction {
                     return Err(RuntimeError::NotEnoughArgument { given: args.len() as u16, expected: n });
                 }
             }
-            AsonExpectedArgs::None => if args.len() > 0 {
+            AsonExpectedArgs::Between(min, max) => {
+                if min > args.len() as u16 {
+                    return Err(RuntimeError::NotEnoughArgument { given: args.len() as u16, expected: min });
+                }
+                if max < args.len() as u16 {
+                    return Err(RuntimeError::TooMuchArgument { given: args.len() as u16, expected: max });
+                }
+            }
+            AsonExpectedArgs::None => if !args.is_empty() {
                 return Err(RuntimeError::TooMuchArgument { given: args.len() as u16, expected: 0 });
             }
         }
 
-        Ok((self.fun)(args, env))
+        let value = (self.fun)(args, env)?;
+        env.check_size(&value)?;
+        Ok(value)
     }
 }
@@ -0,0 +1,231 @@
+use core::fmt;
+
+use crate::ast::{AsonNumber, AsonValue};
+use crate::cst::Document;
+use crate::pattern::Pattern;
+use crate::query::{index_path, member_path};
+use crate::span::Span;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub path: String,
+    pub msg: String,
+    /// Where the offending value is written, when validating a document.
+    /// Values that don't appear in the source as such, like ones from an
+    /// `include` or a spread, point at the closest enclosing value that does.
+    pub span: Option<Span>,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.msg)
+    }
+}
+
+/// A JSON Schema subset: `type`, `enum`, `const`, `required`, `properties`,
+/// `additionalProperties`, `items`, `minItems`/`maxItems`,
+/// `minLength`/`maxLength`, `pattern`, and `minimum`/`maximum` with their
+/// exclusive forms.
+#[derive(Debug)]
+pub struct Schema {
+    root: AsonValue,
+}
+
+impl Schema {
+    pub fn new(root: AsonValue) -> Result<Schema, String> {
+        check_schema(&root)?;
+        Ok(Schema { root })
+    }
+
+    /// Returns every violation found in `value`; an empty list means it is valid.
+    pub fn validate(&self, value: &AsonValue) -> Vec<Violation> {
+        self.check(value).into_iter().map(|(_, violation)| violation).collect()
+    }
+
+    /// Like `validate`, with `value` evaluated from `document` so every
+    /// violation gets the span of the offending value.
+    pub fn validate_document(&self, value: &AsonValue, document: &Document) -> Vec<Violation> {
+        self.check(value)
+            .into_iter()
+            .map(|(pointer, violation)| Violation { span: Some(document.span(&pointer)), ..violation })
+            .collect()
+    }
+
+    /// The violations, each with the JSON pointer of its value.
+    fn check(&self, value: &AsonValue) -> Vec<(String, Violation)> {
+        let mut violations = Vec::new();
+        let location = Location { path: "$".into(), pointer: String::new() };
+        validate(&self.root, value, &location, &mut violations);
+        violations
+    }
+}
+
+/// Where a value is, in the query syntax reported to users and as a JSON pointer.
+struct Location {
+    path: String,
+    pointer: String,
+}
+
+impl Location {
+    fn member(&self, key: &str) -> Location {
+        let token = key.replace('~', "~0").replace('/', "~1");
+        Location { path: member_path(&self.path, key), pointer: format!("{}/{}", self.pointer, token) }
+    }
+
+    fn index(&self, index: usize) -> Location {
+        Location { path: index_path(&self.path, index), pointer: format!("{}/{}", self.pointer, index) }
+    }
+
+    fn violation(&self, msg: String) -> (String, Violation) {
+        (self.pointer.clone(), Violation { path: self.path.clone(), msg, span: None })
+    }
+}
+
+/// Fails on schemas that could never be checked, such as bad patterns.
+fn check_schema(schema: &AsonValue) -> Result<(), String> {
+    match schema {
+        AsonValue::Boolean(_) => Ok(()),
+        AsonValue::Object(m) => {
+            for (key, value) in m {
+                match (key.as_str(), value) {
+                    ("pattern", AsonValue::String(p)) => {
+                        Pattern::new(p).map_err(|e| format!("invalid pattern '{}': {}", p, e))?;
+                    }
+                    ("properties", AsonValue::Object(properties)) => {
+                        properties.values().try_for_each(check_schema)?;
+                    }
+                    ("items" | "additionalProperties", v) => check_schema(v)?,
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+        _ => Err("a schema must be an object or a boolean".into()),
+    }
+}
+
+fn has_type(value: &AsonValue, name: &str) -> bool {
+    match name {
+        "number" => value.is_number(),
+        "integer" => match value {
+            AsonValue::Number(AsonNumber::Integer(_)) => true,
+            AsonValue::Number(AsonNumber::Float(f)) => f.fract() == 0.0,
+            _ => false,
+        },
+        name => value.type_name() == name,
+    }
+}
+
+fn values_equal(a: &AsonValue, b: &AsonValue) -> bool {
+    match (a, b) {
+        (AsonValue::Number(_), AsonValue::Number(_)) => a.as_f64() == b.as_f64(),
+        _ => a == b,
+    }
+}
+
+fn validate(schema: &AsonValue, value: &AsonValue, location: &Location, out: &mut Vec<(String, Violation)>) {
+    let mut report = |msg: String| out.push(location.violation(msg));
+    let keywords = match schema {
+        AsonValue::Boolean(true) => return,
+        AsonValue::Boolean(false) => return report("no value is allowed here".into()),
+        AsonValue::Object(m) => m,
+        _ => return,
+    };
+
+    match keywords.get("type") {
+        Some(AsonValue::String(t)) if !has_type(value, t) => {
+            report(format!("expected {}, got {}", t, value.type_name()));
+        }
+        Some(AsonValue::Array(types)) if !types.iter().any(|t| t.as_str().is_some_and(|t| has_type(value, t))) => {
+            let names: Vec<String> = types.iter().map(|t| t.to_json()).collect();
+            report(format!("expected one of {}, got {}", names.join(", "), value.type_name()));
+        }
+        _ => {}
+    }
+
+    if let Some(AsonValue::Array(options)) = keywords.get("enum")
+        && !options.iter().any(|o| values_equal(o, value))
+    {
+        let names: Vec<String> = options.iter().map(|o| o.to_json()).collect();
+        report(format!("{} is not one of {}", value.to_json(), names.join(", ")));
+    }
+    if let Some(expected) = keywords.get("const")
+        && !values_equal(expected, value)
+    {
+        report(format!("expected {}, got {}", expected.to_json(), value.to_json()));
+    }
+
+    if let Some(n) = value.as_f64() {
+        let bound = |key: &str| keywords.get(key).and_then(|v| v.as_f64());
+        if let Some(min) = bound("minimum").filter(|min| n < *min) {
+            report(format!("{} is less than the minimum of {}", n, min));
+        }
+        if let Some(max) = bound("maximum").filter(|max| n > *max) {
+            report(format!("{} is greater than the maximum of {}", n, max));
+        }
+        if let Some(min) = bound("exclusiveMinimum").filter(|min| n <= *min) {
+            report(format!("{} must be greater than {}", n, min));
+        }
+        if let Some(max) = bound("exclusiveMaximum").filter(|max| n >= *max) {
+            report(format!("{} must be less than {}", n, max));
+        }
+    }
+
+    if let AsonValue::String(s) = value {
+        let len = s.chars().count() as i64;
+        if let Some(min) = keywords.get("minLength").and_then(|v| v.as_i64()).filter(|min| len < *min) {
+            report(format!("string is shorter than {} characters", min));
+        }
+        if let Some(max) = keywords.get("maxLength").and_then(|v| v.as_i64()).filter(|max| len > *max) {
+            report(format!("string is longer than {} characters", max));
+        }
+        if let Some(AsonValue::String(p)) = keywords.get("pattern")
+            && let Ok(pattern) = Pattern::new(p)
+        {
+            match pattern.is_match(s) {
+                Ok(true) => {}
+                Ok(false) => report(format!("\"{}\" does not match the pattern '{}'", s, p)),
+                Err(e) => report(format!("the pattern '{}' gave up: {}", p, e)),
+            }
+        }
+    }
+
+    if let AsonValue::Array(elements) = value {
+        let len = elements.len() as i64;
+        if let Some(min) = keywords.get("minItems").and_then(|v| v.as_i64()).filter(|min| len < *min) {
+            report(format!("array has fewer than {} items", min));
+        }
+        if let Some(max) = keywords.get("maxItems").and_then(|v| v.as_i64()).filter(|max| len > *max) {
+            report(format!("array has more than {} items", max));
+        }
+        if let Some(items) = keywords.get("items") {
+            for (i, element) in elements.iter().enumerate() {
+                validate(items, element, &location.index(i), out);
+            }
+        }
+    }
+
+    if let AsonValue::Object(members) = value {
+        if let Some(AsonValue::Array(required)) = keywords.get("required") {
+            for key in required.iter().filter_map(|k| k.as_str()) {
+                if !members.contains_key(key) {
+                    out.push(location.violation(format!("missing required member \"{}\"", key)));
+                }
+            }
+        }
+        let properties = keywords.get("properties").and_then(|p| p.as_object());
+        let mut keys: Vec<&String> = members.keys().collect();
+        keys.sort();
+        for key in keys {
+            let member = location.member(key);
+            match properties.and_then(|p| p.get(key)) {
+                Some(property) => validate(property, &members[key], &member, out),
+                None => match keywords.get("additionalProperties") {
+                    Some(AsonValue::Boolean(false)) => out.push(member.violation("additional member is not allowed".into())),
+                    Some(additional) => validate(additional, &members[key], &member, out),
+                    None => {}
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+
+use serde::ser::{self, Serialize};
+
+use crate::ast::{AsonNumber, AsonValue};
+use crate::de::Error;
+
+/// Serializes `value` as ASON source text.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    Ok(to_value(value)?.to_ason())
+}
+
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<AsonValue, Error> {
+    value.serialize(ValueSerializer)
+}
+
+impl Serialize for AsonValue {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            AsonValue::Function(_) => Err(ser::Error::custom("functions cannot be serialized")),
+            AsonValue::Object(m) => serializer.collect_map(m),
+            AsonValue::Array(a) => serializer.collect_seq(a),
+            AsonValue::String(s) => serializer.serialize_str(s),
+            AsonValue::Number(AsonNumber::Integer(i)) => serializer.serialize_i64(*i),
+            AsonValue::Number(AsonNumber::Float(f)) => serializer.serialize_f64(*f),
+            AsonValue::Boolean(b) => serializer.serialize_bool(*b),
+            AsonValue::Null => serializer.serialize_unit(),
+        }
+    }
+}
+
+/// Builds an `AsonValue` out of any `Serialize` type.
+pub struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = AsonValue;
+    type Error = Error;
+
+    type SerializeSeq = SerializeVec;
+    type SerializeTuple = SerializeVec;
+    type SerializeTupleStruct = SerializeVec;
+    type SerializeTupleVariant = SerializeVariant<SerializeVec>;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeVariant<SerializeMap>;
+
+    fn serialize_bool(self, v: bool) -> Result<AsonValue, Error> {
+        Ok(AsonValue::Boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<AsonValue, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<AsonValue, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<AsonValue, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<AsonValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<AsonValue, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<AsonValue, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<AsonValue, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<AsonValue, Error> {
+        match i64::try_from(v) {
+            Ok(i) => Ok(i.into()),
+            Err(_) => Err(ser::Error::custom(format!("integer {} is out of range", v))),
+        }
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<AsonValue, Error> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<AsonValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_char(self, v: char) -> Result<AsonValue, Error> {
+        Ok(v.to_string().into())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<AsonValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<AsonValue, Error> {
+        Ok(AsonValue::Array(v.iter().map(|b| (*b as i64).into()).collect()))
+    }
+
+    fn serialize_none(self) -> Result<AsonValue, Error> {
+        Ok(AsonValue::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<AsonValue, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<AsonValue, Error> {
+        Ok(AsonValue::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<AsonValue, Error> {
+        Ok(AsonValue::Null)
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<AsonValue, Error> {
+        Ok(variant.into())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<AsonValue, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<AsonValue, Error> {
+        let mut members = HashMap::new();
+        members.insert(variant.to_string(), value.serialize(self)?);
+        Ok(AsonValue::Object(members))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeVec, Error> {
+        Ok(SerializeVec { elements: Vec::with_capacity(len.unwrap_or(0)) })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeVec, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeVec, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeVec>, Error> {
+        Ok(SerializeVariant { variant, inner: self.serialize_seq(Some(len))? })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeMap, Error> {
+        Ok(SerializeMap { members: HashMap::new(), key: None })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeMap>, Error> {
+        Ok(SerializeVariant { variant, inner: self.serialize_map(Some(len))? })
+    }
+}
+
+pub struct SerializeVec {
+    elements: Vec<AsonValue>,
+}
+
+impl ser::SerializeSeq for SerializeVec {
+    type Ok = AsonValue;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.elements.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<AsonValue, Error> {
+        Ok(AsonValue::Array(self.elements))
+    }
+}
+
+impl ser::SerializeTuple for SerializeVec {
+    type Ok = AsonValue;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<AsonValue, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeVec {
+    type Ok = AsonValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<AsonValue, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+pub struct SerializeMap {
+    members: HashMap<String, AsonValue>,
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = AsonValue;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        match key.serialize(ValueSerializer)? {
+            AsonValue::String(s) => self.key = Some(s),
+            AsonValue::Number(n) => self.key = Some(n.to_string()),
+            AsonValue::Boolean(b) => self.key = Some(b.to_string()),
+            _ => return Err(ser::Error::custom("object keys must be strings")),
+        }
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let Some(key) = self.key.take() else {
+            return Err(ser::Error::custom("serialize_value called before serialize_key"));
+        };
+        self.members.insert(key, value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<AsonValue, Error> {
+        Ok(AsonValue::Object(self.members))
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = AsonValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.members.insert(key.to_string(), value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<AsonValue, Error> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+/// Wraps the serialized fields of an enum variant as `} "Variant" ... {`.
+pub struct SerializeVariant<T> {
+    variant: &'static str,
+    inner: T,
+}
+
+impl<T> SerializeVariant<T> {
+    fn wrap(variant: &str, value: AsonValue) -> AsonValue {
+        let mut members = HashMap::new();
+        members.insert(variant.to_string(), value);
+        AsonValue::Object(members)
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeVec> {
+    type Ok = AsonValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<AsonValue, Error> {
+        Ok(Self::wrap(self.variant, ser::SerializeSeq::end(self.inner)?))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeMap> {
+    type Ok = AsonValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<AsonValue, Error> {
+        Ok(Self::wrap(self.variant, ser::SerializeMap::end(self.inner)?))
+    }
+}
@@ -9,6 +9,7 @@ pub enum TokenKind {
   CloseExpr, // '('
   Colon, // ':'
   Comma, // ','
+  Spread, // '...'
   Symbol(String),
   StringLiteral(String), // regex: "[^"]*"
   IntegerLiteral(i64), // regex: [0-9]+
@@ -18,14 +19,52 @@ pub enum TokenKind {
   Null, // 'null'
 }
 
+use crate::span::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriviaKind {
+  LineComment, // '\\ ...'
+  DocComment, // '\\\ ...'
+  BlockComment, // '\* ... *\'
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia<'a> {
+  pub kind: TriviaKind,
+  pub text: &'a str, // the whole comment, markers included
+  pub span: Span
+}
+
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct Token<'a> {
   pub kind: TokenKind,
   pub lexem: &'a str,
-  pub line: usize,
-  pub column: usize,
-  pub index: usize
+  pub line: usize, // line of the first character
+  pub column: usize, // column of the first character
+  pub span: Span,
+  pub leading: Vec<Trivia<'a>>, // comments before the token
+  pub trailing: Vec<Trivia<'a>> // comments after the token on the same line
+}
+
+impl Token<'_> {
+  /// The text of the doc-comments right before this token, without the
+  /// `\\\` markers.
+  pub fn doc(&self) -> Option<String> {
+    let lines: Vec<&str> = self.leading
+      .iter()
+      .filter(|t| t.kind == TriviaKind::DocComment)
+      .map(|t| {
+        let text = &t.text[3..];
+        text.strip_prefix(' ').unwrap_or(text)
+      })
+      .collect();
+    if lines.is_empty() {
+      None
+    } else {
+      Some(lines.join("\n"))
+    }
+  }
 }
 
 pub type TokenList<'a> = Vec<Token<'a>>;
@@ -0,0 +1,171 @@
+use crate::ast::{AsonExpr, AsonValue};
+use crate::environment::Environment;
+use crate::runtime::{AsonFunction, RuntimeError};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    /// Pushes `constants[i]`.
+    Constant(u32),
+    /// Pushes the value bound to the symbol in slot `i`, looked up when it runs.
+    Load(u32),
+    /// Pushes the `null` of an empty expression-s.
+    Null,
+    /// Starts an expression-s, whose arguments follow.
+    Enter,
+    /// Calls the function in slot `callee` with the top `argc` values.
+    Call { callee: u32, argc: u32 },
+}
+
+/// An expression-s compiled to a flat list of stack operations. Every
+/// symbol it names gets a slot, and the function in a slot is looked up once
+/// per run instead of on every call. Values aren't cached: the code has no
+/// loops, so each `Load` runs once, and a cached copy would cost a clone more
+/// than the lookup it saves.
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    code: Vec<Op>,
+    constants: Vec<AsonValue>,
+    names: Vec<String>,
+}
+
+impl Program {
+    pub fn compile(expr: &AsonExpr) -> Program {
+        let mut program = Program::default();
+        program.emit(expr);
+        program
+    }
+
+    /// Like `compile`, reusing the buffers of `self`.
+    pub fn recompile(&mut self, expr: &AsonExpr) {
+        self.code.clear();
+        self.constants.clear();
+        self.names.clear();
+        self.emit(expr);
+    }
+
+    pub fn code(&self) -> &[Op] {
+        &self.code
+    }
+
+    /// The symbol of each slot.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    fn emit(&mut self, expr: &AsonExpr) {
+        match expr {
+            AsonExpr::Value(value, _) => {
+                self.code.push(Op::Constant(self.constants.len() as u32));
+                self.constants.push(value.clone());
+            }
+            AsonExpr::Symbol(name, _) => {
+                let slot = self.slot(name);
+                self.code.push(Op::Load(slot));
+            }
+            AsonExpr::ExprS(args, callee, _) => {
+                self.code.push(Op::Enter);
+                for arg in args {
+                    self.emit(arg);
+                }
+                let callee = self.slot(callee);
+                self.code.push(Op::Call { callee, argc: args.len() as u32 });
+            }
+            AsonExpr::None(_) => self.code.push(Op::Null),
+        }
+    }
+
+    fn slot(&mut self, name: &str) -> u32 {
+        match self.names.iter().position(|n| n == name) {
+            Some(slot) => slot as u32,
+            None => {
+                self.names.push(name.to_string());
+                self.names.len() as u32 - 1
+            }
+        }
+    }
+}
+
+/// Runs programs on a value stack. It gives the same results, errors and
+/// step counts as `AsonExpr::eval`, which stays as the reference.
+#[derive(Debug, Default)]
+pub struct Vm {
+    stack: Vec<AsonValue>,
+    /// The function of each slot, once called.
+    functions: Vec<Option<AsonFunction>>,
+    /// Expression-s entered and not called yet.
+    open: usize,
+}
+
+impl Vm {
+    pub fn new() -> Self {
+        Vm::default()
+    }
+
+    pub fn run(&mut self, program: &Program, env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+        self.stack.clear();
+        self.functions.clear();
+        self.functions.resize(program.names.len(), None);
+        self.open = 0;
+        let result = self.execute(program, env);
+        // Leave what an error interrupted, like the tree-walker does.
+        for _ in 0..self.open {
+            env.leave();
+        }
+        result
+    }
+
+    fn execute(&mut self, program: &Program, env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+        let mut generation = env.generation();
+        for op in &program.code {
+            match *op {
+                Op::Constant(i) => {
+                    env.step()?;
+                    self.stack.push(program.constants[i as usize].clone());
+                }
+                Op::Load(slot) => {
+                    env.step()?;
+                    let value = env.symbols.get(&program.names[slot as usize]).ok_or(RuntimeError::UndefinedSymbol)?;
+                    self.stack.push(value.clone());
+                }
+                Op::Null => {
+                    env.step()?;
+                    self.stack.push(AsonValue::Null);
+                }
+                Op::Enter => {
+                    env.step()?;
+                    env.enter()?;
+                    self.open += 1;
+                }
+                Op::Call { callee, argc } => {
+                    let function = self.function(program, callee, env)?;
+                    let base = self.stack.len() - argc as usize;
+                    let result = function.call(&self.stack[base..], env)?;
+                    self.stack.truncate(base);
+                    self.stack.push(result);
+                    env.leave();
+                    self.open -= 1;
+                    // The call may have bound symbols, such as with `defvar`.
+                    if env.generation() != generation {
+                        generation = env.generation();
+                        self.functions.iter_mut().for_each(|f| *f = None);
+                    }
+                }
+            }
+        }
+        Ok(self.stack.pop().unwrap_or(AsonValue::Null))
+    }
+
+    fn function(&mut self, program: &Program, slot: u32, env: &Environment) -> Result<AsonFunction, RuntimeError> {
+        if let Some(function) = &self.functions[slot as usize] {
+            return Ok(function.clone());
+        }
+        match env.symbols.get(&program.names[slot as usize]) {
+            Some(AsonValue::Function(function)) => {
+                self.functions[slot as usize] = Some(function.clone());
+                Ok(function.clone())
+            }
+            Some(_) => Err(RuntimeError::NotAFunction),
+            None => Err(RuntimeError::UndefinedSymbol),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use ason::binary::{from_cbor, from_msgpack, to_cbor, to_msgpack};
+use ason::{AsonNumber, AsonValue};
+
+fn sample() -> AsonValue {
+    AsonValue::from_ason_string(
+        r#"}
+  "name" "svc"
+  "port" 8080
+  "ratio" 1.0
+  "offset" )0 40000 -(
+  "enabled" true
+  "fallback" null
+  "tags" ]"a" "b" "é"[
+  "nested" } "empty" ][ "inner" }{ {
+{"#,
+    )
+    .unwrap()
+}
+
+const INTEGERS: &[i64] = &[
+    0, 1, 23, 24, 127, 128, 255, 256, 65535, 65536, 4294967295, 4294967296, i64::MAX,
+    -1, -24, -25, -32, -33, -128, -129, -32768, -32769, -2147483648, -2147483649, i64::MIN,
+];
+
+#[test]
+fn msgpack_round_trip() {
+    let value = sample();
+    assert_eq!(from_msgpack(&to_msgpack(&value)).unwrap(), value);
+}
+
+#[test]
+fn cbor_round_trip() {
+    let value = sample();
+    assert_eq!(from_cbor(&to_cbor(&value)).unwrap(), value);
+}
+
+#[test]
+fn integers_and_floats_stay_apart() {
+    for &i in INTEGERS {
+        let integer = AsonValue::from(i);
+        assert_eq!(from_msgpack(&to_msgpack(&integer)).unwrap(), integer);
+        assert_eq!(from_cbor(&to_cbor(&integer)).unwrap(), integer);
+    }
+    let float = AsonValue::from(2.0);
+    assert_eq!(from_msgpack(&to_msgpack(&float)).unwrap().as_number(), Some(&AsonNumber::Float(2.0)));
+    assert_eq!(from_cbor(&to_cbor(&float)).unwrap().as_number(), Some(&AsonNumber::Float(2.0)));
+}
+
+#[test]
+fn long_strings_and_arrays() {
+    for len in [31, 32, 255, 256, 65536] {
+        let value = AsonValue::Array(vec![AsonValue::from("x".repeat(len)); len.min(300)]);
+        assert_eq!(from_msgpack(&to_msgpack(&value)).unwrap(), value);
+        assert_eq!(from_cbor(&to_cbor(&value)).unwrap(), value);
+    }
+}
+
+#[test]
+fn known_encodings() {
+    assert_eq!(to_msgpack(&AsonValue::from(-33)), [0xd0, 0xdf]);
+    assert_eq!(to_msgpack(&AsonValue::from(300)), [0xcd, 0x01, 0x2c]);
+    assert_eq!(to_cbor(&AsonValue::from(-500)), [0x39, 0x01, 0xf3]);
+    assert_eq!(to_cbor(&AsonValue::from("IETF")), [0x64, 0x49, 0x45, 0x54, 0x46]);
+}
+
+#[test]
+fn decodes_other_encoders_output() {
+    // Half and single precision floats, and indefinite lengths from RFC 8949.
+    assert_eq!(from_cbor(&[0xf9, 0x3e, 0x00]).unwrap(), AsonValue::from(1.5));
+    assert_eq!(from_cbor(&[0xfa, 0x47, 0xc3, 0x50, 0x00]).unwrap(), AsonValue::from(100000.0));
+    assert_eq!(from_cbor(&[0x9f, 0x01, 0x02, 0xff]).unwrap(), AsonValue::from(vec![1, 2]));
+    assert_eq!(from_cbor(&[0x7f, 0x62, 0x61, 0x62, 0x61, 0x63, 0xff]).unwrap(), AsonValue::from("abc"));
+    // An epoch time tag around an integer.
+    assert_eq!(from_cbor(&[0xc1, 0x1a, 0x51, 0x4b, 0x67, 0xb0]).unwrap(), AsonValue::from(1363896240));
+    assert_eq!(from_msgpack(&[0xca, 0x3f, 0xc0, 0x00, 0x00]).unwrap(), AsonValue::from(1.5));
+}
+
+#[test]
+fn rejects_what_ason_cannot_hold() {
+    assert!(from_msgpack(&[0xc4, 0x01, 0x00]).is_err());
+    assert!(from_msgpack(&[0x81, 0x01, 0x02]).is_err());
+    assert!(from_msgpack(&[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).is_err());
+    assert!(from_cbor(&[0x41, 0x00]).is_err());
+    assert!(from_cbor(&[0x3b, 0x80, 0, 0, 0, 0, 0, 0, 0]).is_err());
+    assert!(from_msgpack(&[0x92, 0x01]).is_err());
+    assert!(from_cbor(&[0x01, 0x02]).is_err());
+    assert!(from_cbor(&[0x81; 1000]).is_err());
+}
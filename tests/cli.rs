@@ -0,0 +1,159 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use ason::AsonValue;
+
+/// Writes `source` to a file of its own and runs the `ason` binary on it.
+fn run(name: &str, source: &str, args: &[&str]) -> Output {
+    let path = file(name, source);
+    Command::new(env!("CARGO_BIN_EXE_ason")).args(args).arg(&path).output().unwrap()
+}
+
+fn file(name: &str, source: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("ason-cli-{}-{}", std::process::id(), name));
+    std::fs::write(&path, source).unwrap();
+    path
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn runtime_errors_are_located() {
+    let cases = [
+        ("add.ason", "]\n  )1 \"a\" +(\n[", ":2:3: + expects numbers, got string."),
+        ("sub.ason", ")1 null -(", ":1:1: - expects numbers, got null."),
+        ("mul.ason", ")true 2 *(", ":1:1: * expects numbers, got boolean."),
+        ("div.ason", ")\"a\" 2 /(", ":1:1: / expects numbers, got string."),
+        ("read.ason", ")1 read-file-to-string(", ":1:1: read-file-to-string expects a string, got integer."),
+        ("defvar.ason", ")1 2 defvar(", ":1:1: defvar expects a string, got integer."),
+        ("overflow.ason", ")9223372036854775807 1 +(", ":1:1: Integer overflow in +."),
+    ];
+    for (name, source, error) in cases {
+        let output = run(name, source, &[]);
+        assert_eq!(output.status.code(), Some(1), "{}", name);
+        let stderr = stderr(&output);
+        assert!(stderr.starts_with("Error ") && stderr.contains(&format!("{}{}", name, error)), "{}", stderr);
+    }
+}
+
+#[test]
+fn limits_are_reported() {
+    let nested = format!("{}{}", "]".repeat(300), "[".repeat(300));
+    let output = run("deep.ason", &nested, &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Maximum nesting depth of 256 exceeded."));
+}
+
+#[test]
+fn set_values_keep_literal_types() {
+    let source = "] name replicas ratio debug missing quoted mode [";
+    let sets = [
+        "name=api", "replicas=3", "ratio=0.5", "debug=true", "missing=null", "quoted=\"3\"", "mode=filter",
+    ];
+    let mut args = Vec::new();
+    for set in &sets {
+        args.extend(["--set", set]);
+    }
+    let output = run("set.ason", source, &args);
+    assert_eq!(stderr(&output), "");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let value = AsonValue::from_json_string(&stdout).unwrap();
+    assert_eq!(value, AsonValue::from(vec![
+        AsonValue::from("api"),
+        AsonValue::from(3),
+        AsonValue::from(0.5),
+        AsonValue::from(true),
+        AsonValue::Null,
+        AsonValue::from("3"),
+        AsonValue::from("filter"),
+    ]));
+}
+
+#[test]
+fn var_files_define_their_members() {
+    let vars = file("vars.json", r#"{"name": "api", "port": 8080}"#);
+    let output = run("var-file.ason", "] name port [", &["--var-file", vars.to_str().unwrap()]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).split_whitespace().collect::<String>(), r#"["api",8080]"#);
+}
+
+#[test]
+fn validate_reports_locations() {
+    let schema = file("schema.json", r#"{"properties": {"port": {"type": "integer"}, "name": {"pattern": "^(a+)+$"}}}"#);
+    let name = format!("{}b", "a".repeat(30));
+    let source = format!("}}\n  \"port\" \"80\"\n  \"name\" \"{}\"\n{{", name);
+    let output = run("validate.ason", &source, &["validate", "--schema", schema.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = stderr(&output);
+    assert!(stderr.contains("validate.ason:2:10: $.port: expected integer, got string"), "{}", stderr);
+    assert!(stderr.contains("validate.ason:3:10: $.name: "), "{}", stderr);
+}
+
+#[test]
+fn duplicate_key_policies() {
+    let source = "} \"a\" 1 \"a\" 2 {";
+    let output = run("duplicate.ason", source, &[]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).split_whitespace().collect::<String>(), r#"{"a":2}"#);
+    assert!(stderr(&output).contains("duplicate.ason:1:9: Duplicate key \"a\", first defined at 1:3."), "{}", stderr(&output));
+
+    let output = run("duplicate-error.ason", source, &["--duplicate-keys", "error"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Duplicate key \"a\""));
+
+    let output = run("duplicate-first.ason", source, &["--duplicate-keys", "first-wins"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).split_whitespace().collect::<String>(), r#"{"a":1}"#);
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn stream_prints_one_line_per_document() {
+    let output = run("stream.ason", "} \"a\" 1 {\n] 2 [\n\"three\"\n", &["--stream"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "{\"a\":1}\n[2]\n\"three\"\n");
+
+    let output = run("no-stream.ason", "} \"a\" 1 {\n] 2 [\n", &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("no-stream.ason:2:1: Unexpected token after the value: ]"), "{}", stderr(&output));
+}
+
+#[test]
+fn formats() {
+    let source = "} \"b\" ] 1 2 [ \"a\" \"x\" {";
+    let output = run("format.ason", source, &["--format", "yaml"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "a: x\nb:\n  - 1\n  - 2\n");
+    let output = run("format-canonical.ason", source, &["--format", "canonical-json"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "{\"a\":\"x\",\"b\":[1,2]}\n");
+
+    let output = run("format-toml.ason", "} \"a\" null {", &["--format", "toml"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("null at $.a can't be written as TOML"), "{}", stderr(&output));
+}
+
+#[test]
+fn deep_json_inputs_are_errors() {
+    let deep = file("deep.json", &"[".repeat(200_000));
+    let output = run("deep-vars.ason", "x", &["--var-file", deep.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Nesting deeper than 256 levels."), "{}", stderr(&output));
+}
+
+#[test]
+fn readme_collections_example() {
+    let readme = include_str!("../README.org");
+    let command = "ason eval --var-file regions.json service.ason";
+    let service = "}\n  \"hosts\" ))regions concat \"-svc\" map( concat \".example.com\" map(\n{\n";
+    let regions = r#"{"regions": ["eu", "us"]}"#;
+    assert!(readme.contains(&format!("{}\n", command)) && readme.contains(service) && readme.contains(regions), "the README example changed");
+
+    let dir = std::env::temp_dir().join(format!("ason-cli-{}-readme", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("regions.json"), regions).unwrap();
+    std::fs::write(dir.join("service.ason"), service).unwrap();
+    let args: Vec<&str> = command.split(' ').skip(1).collect();
+    let output = Command::new(env!("CARGO_BIN_EXE_ason")).args(args).current_dir(&dir).output().unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "{\n  \"hosts\": [\n    \"eu-svc.example.com\",\n    \"us-svc.example.com\"\n  ]\n}\n"
+    );
+}
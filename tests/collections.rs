@@ -0,0 +1,83 @@
+use ason::runtime::{AsonExpectedArgs, RuntimeError};
+use ason::{AsonValue, Environment};
+
+fn json(s: &str) -> AsonValue {
+    AsonValue::from_json_string(s).unwrap()
+}
+
+/// `)n even?(`, since there are no comparison builtins to filter with.
+fn even(args: &[AsonValue], _env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    Ok(AsonValue::Boolean(args[0].as_i64().is_some_and(|n| n % 2 == 0)))
+}
+
+/// `)n pair(` gives `[n, n]`.
+fn pair(args: &[AsonValue], _env: &mut Environment) -> Result<AsonValue, RuntimeError> {
+    Ok(AsonValue::Array(vec![args[0].clone(), args[0].clone()]))
+}
+
+/// Evaluates `source` with `numbers`, `names`, `none` and `ports` bound, since
+/// containers can't be written inside an expression-s.
+fn evaluate(source: &str) -> Result<AsonValue, String> {
+    let mut env = Environment::new();
+    env.define_function("even?".into(), even, AsonExpectedArgs::Exact(1));
+    env.define_function("pair".into(), pair, AsonExpectedArgs::Exact(1));
+    env.add_constant("numbers".into(), json("[3, 1, 4, 1, 5]"));
+    env.add_constant("names".into(), json(r#"["web", "api", "db"]"#));
+    env.add_constant("none".into(), json("[]"));
+    env.add_constant("ports".into(), json(r#"{"web": 80, "api": 8080}"#));
+    AsonValue::from_ason_string_in(source, &mut env).map_err(|e| e.msg)
+}
+
+#[test]
+fn builtins() {
+    let cases = [
+        (")names concat \"-svc\" map(", r#"["web-svc", "api-svc", "db-svc"]"#),
+        (")numbers even? filter(", "[4]"),
+        (")numbers + 0 reduce(", "14"),
+        (")numbers + 10 1 reduce(", "29"),
+        (")numbers even? any?(", "true"),
+        (")numbers even? all?(", "false"),
+        (")none even? all?(", "true"),
+        (")none even? any?(", "false"),
+        (")names concat sort-by(", r#"["api", "db", "web"]"#),
+        (")numbers + 0 sort-by(", "[1, 1, 3, 4, 5]"),
+        (")numbers even? group-by(", r#"{"false": [3, 1, 1, 5], "true": [4]}"#),
+        (")names numbers zip(", r#"[["web", 3], ["api", 1], ["db", 4]]"#),
+        (")numbers zip(", "[[3], [1], [4], [1], [5]]"),
+        (")names pair flat-map(", r#"["web", "web", "api", "api", "db", "db"]"#),
+        (")ports + 1 map-values(", r#"{"web": 81, "api": 8081}"#),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(evaluate(source).unwrap(), json(expected), "{}", source);
+    }
+}
+
+#[test]
+fn functions_can_be_bound() {
+    assert_eq!(evaluate("] )concat \"join\" defvar( )names join \"!\" map( [").unwrap()[1], json(r#"["web!", "api!", "db!"]"#));
+}
+
+#[test]
+fn errors() {
+    let cases = [
+        (")1 concat map(", "map expects an array, got integer."),
+        (")names \"concat\" map(", "map expects a function, got string."),
+        (")numbers concat flat-map(", "flat-map expects the function to return arrays, got string."),
+        (")names ports zip(", "zip expects an array, got object."),
+        (")numbers even? sort-by(", "sort-by can't compare boolean with boolean."),
+        (")names ports map-values(", "map-values expects an object, got array."),
+        (")names + 1 map(", "+ expects numbers, got string."),
+    ];
+    for (source, expected) in cases {
+        let error = evaluate(source).unwrap_err();
+        assert_eq!(error, expected, "{}", source);
+    }
+}
+
+#[test]
+fn calls_count_as_steps() {
+    let mut env = Environment::with_limits(ason::limits::Limits { max_steps: 6, ..Default::default() });
+    env.add_constant("numbers".into(), json("[1, 2, 3, 4, 5, 6, 7, 8]"));
+    let error = AsonValue::from_ason_string_in(")numbers concat map(", &mut env).unwrap_err();
+    assert_eq!(error.msg, "Evaluation step budget of 6 exhausted.");
+}
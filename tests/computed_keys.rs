@@ -0,0 +1,25 @@
+use ason::AsonValue;
+
+fn evaluate(source: &str) -> Result<AsonValue, String> {
+    AsonValue::from_ason_string(source).map_err(|e| e.to_string())
+}
+
+fn json(s: &str) -> AsonValue {
+    AsonValue::from_json_string(s).unwrap()
+}
+
+#[test]
+fn computed_keys() {
+    let source = r#"] )"api" "name" defvar( } )"svc-" name concat( 8080 name "up" "port": 1 { ["#;
+    assert_eq!(evaluate(source).unwrap(), json(r#"["api", {"svc-api": 8080, "api": "up", "port": 1}]"#));
+}
+
+#[test]
+fn computed_keys_must_be_strings() {
+    assert_eq!(evaluate("} )1 2 +( \"three\" {").unwrap_err(), "<string>:1:3: Object keys must be strings, got integer.");
+    assert_eq!(
+        evaluate("] )null \"n\" defvar( } n 1 { [").unwrap_err(),
+        "<string>:1:23: Object keys must be strings, got null."
+    );
+    assert_eq!(evaluate("} undefined 1 {").unwrap_err(), "<string>:1:3: Undefined symbol: undefined");
+}
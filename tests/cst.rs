@@ -0,0 +1,81 @@
+use ason::cst::{Document, NodeKind, PieceKind};
+use ason::token::TriviaKind;
+use ason::AsonValue;
+
+const SOURCE: &str = r#"}
+  \\ The release
+  "version": "1.2.3",
+  "tags": ] "beta", "rc" [,
+  "deps": } "serde" "1" "left-pad" "2" { \\ keep sorted
+{
+"#;
+
+#[test]
+fn parse_keeps_every_byte() {
+    let doc = Document::parse(SOURCE).unwrap();
+    assert_eq!(doc.to_string(), SOURCE);
+    let NodeKind::Object { members, commas } = &doc.root().kind else { panic!("expected an object") };
+    assert!(commas);
+    assert_eq!(members.iter().map(|m| m.key.as_str()).collect::<Vec<_>>(), ["version", "tags", "deps"]);
+    assert_eq!(&SOURCE[members[0].start..members[0].value.span.end], "\\\\ The release\n  \"version\": \"1.2.3\"");
+}
+
+#[test]
+fn set_member() {
+    let mut doc = Document::parse(SOURCE).unwrap();
+    doc.set_member("", "version", &"1.2.4".into()).unwrap();
+    assert_eq!(doc.to_string(), SOURCE.replace("1.2.3", "1.2.4"));
+
+    doc.set_member("", "name", &"crate".into()).unwrap();
+    assert!(doc.to_string().ends_with("{, \\\\ keep sorted\n  \"name\": \"crate\"\n{\n"), "{}", doc);
+}
+
+#[test]
+fn remove_member() {
+    let mut doc = Document::parse(SOURCE).unwrap();
+    doc.remove_member("/deps", "left-pad").unwrap();
+    assert_eq!(doc.to_string(), SOURCE.replace("\"left-pad\" \"2\" ", ""));
+    doc.remove_member("", "version").unwrap();
+    assert_eq!(doc.to_string(), SOURCE.replace("\"left-pad\" \"2\" ", "").replace("  \\\\ The release\n  \"version\": \"1.2.3\",\n", ""));
+    assert_eq!(doc.remove_member("", "version").unwrap_err().to_string(), "'' has no member \"version\"");
+}
+
+#[test]
+fn insert_array_element() {
+    let mut doc = Document::parse(SOURCE).unwrap();
+    doc.insert_array_element("/tags", 0, &"stable".into()).unwrap();
+    doc.insert_array_element("/tags", 3, &AsonValue::Null).unwrap();
+    assert_eq!(doc.to_string(), SOURCE.replace("] \"beta\", \"rc\" [", "] \"stable\", \"beta\", \"rc\", null ["));
+    assert!(doc.insert_array_element("/tags", 9, &AsonValue::Null).is_err());
+    assert!(doc.insert_array_element("/version", 0, &AsonValue::Null).is_err());
+
+    let mut empty = Document::parse("] [").unwrap();
+    empty.insert_array_element("", 0, &1.into()).unwrap();
+    assert_eq!(empty.to_string(), "] 1 [");
+}
+
+#[test]
+fn pieces_cover_the_source() {
+    let doc = Document::parse(SOURCE).unwrap();
+    let pieces = doc.pieces();
+    let text: String = pieces.iter().map(|p| &SOURCE[p.span.start..p.span.end]).collect();
+    assert_eq!(text, SOURCE);
+    let comments: Vec<&str> = pieces
+        .iter()
+        .filter(|p| p.kind == PieceKind::Comment(TriviaKind::LineComment))
+        .map(|p| &SOURCE[p.span.start..p.span.end])
+        .collect();
+    assert_eq!(comments, ["\\\\ The release", "\\\\ keep sorted"]);
+    assert!(pieces.windows(2).all(|w| !(w[0].kind == PieceKind::Whitespace && w[1].kind == PieceKind::Whitespace)));
+}
+
+#[test]
+fn nesting_depth() {
+    let nested = format!("{}{}", "]".repeat(200_000), "[".repeat(200_000));
+    assert_eq!(Document::parse(&nested).unwrap_err().to_string(), "1:257: Maximum nesting depth of 256 exceeded.");
+    let spreads = format!("] {}1 [", "...".repeat(200_000));
+    assert!(Document::parse(&spreads).is_err());
+
+    let mut doc = Document::parse(&format!("{}{}", "]".repeat(256), "[".repeat(256))).unwrap();
+    assert!(doc.insert_array_element(&"/0".repeat(255), 0, &AsonValue::Array(vec![])).is_err());
+}
@@ -0,0 +1,95 @@
+use std::time::{Duration, Instant};
+
+use ason::diff::{diff, Change};
+use ason::patch::{apply_patch, merge_patch};
+use ason::AsonValue;
+
+fn json(s: &str) -> AsonValue {
+    AsonValue::from_json_string(s).unwrap()
+}
+
+fn changes(old: &str, new: &str) -> Vec<String> {
+    diff(&json(old), &json(new)).iter().map(|c| c.to_string()).collect()
+}
+
+#[test]
+fn objects() {
+    assert!(changes(r#"{"a": 1}"#, r#"{"a": 1}"#).is_empty());
+    assert_eq!(changes(r#"{"a": 1, "b": 2, "c": {"d": 3}}"#, r#"{"a": 1, "c": {"d": 4}, "e": 5}"#), [
+        "- $.b: 2",
+        "~ $.c.d: 3 -> 4",
+        "+ $.e: 5",
+    ]);
+    assert_eq!(changes(r#"{"a": 1}"#, "[1]"), [r#"~ $: {"a":1} -> [1]"#]);
+}
+
+#[test]
+fn arrays_are_aligned() {
+    assert_eq!(changes("[1, 2, 3]", "[1, 9, 2, 3]"), ["+ $[1]: 9"]);
+    assert_eq!(changes("[1, 2, 3]", "[1, 3]"), ["- $[1]: 2"]);
+    assert_eq!(changes("[1, 2, 3]", "[1, 5, 3]"), ["~ $[1]: 2 -> 5"]);
+    assert_eq!(changes(r#"[{"a": 1}, 2]"#, r#"[{"a": 2}, 2]"#), ["~ $[0].a: 1 -> 2"]);
+    assert_eq!(changes("[]", "[1, 2]"), ["+ $[0]: 1", "+ $[1]: 2"]);
+}
+
+#[test]
+fn large_arrays_use_bounded_memory() {
+    let started = Instant::now();
+    let a = AsonValue::Array((0..100_000).map(AsonValue::from).collect());
+    let b = AsonValue::Array((0..100_000).map(|i| AsonValue::from(if i % 1000 == 0 { -i } else { i })).collect());
+    let found = diff(&a, &b);
+    assert_eq!(found.len(), 99);
+    assert_eq!(found[0], Change::Changed { path: "$[1000]".into(), old: 1000.into(), new: (-1000).into() });
+    // Common ends are trimmed, so a single insertion stays one change.
+    let mut c = a.as_array().unwrap().clone();
+    c.insert(50_000, AsonValue::from("new"));
+    assert_eq!(diff(&a, &AsonValue::Array(c)), [Change::Added { path: "$[50000]".into(), value: "new".into() }]);
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn merge_patches() {
+    let mut target = json(r#"{"a": "b", "c": {"d": "e", "f": "g"}}"#);
+    merge_patch(&mut target, &json(r#"{"a": "z", "c": {"f": null}}"#));
+    assert_eq!(target, json(r#"{"a": "z", "c": {"d": "e"}}"#));
+    merge_patch(&mut target, &json("[1]"));
+    assert_eq!(target, json("[1]"));
+}
+
+#[test]
+fn json_patches() {
+    let mut target = json(r#"{"foo": ["bar", "baz"], "n": 1}"#);
+    apply_patch(&mut target, &json(r#"[
+        {"op": "add", "path": "/foo/1", "value": "qux"},
+        {"op": "remove", "path": "/foo/0"},
+        {"op": "replace", "path": "/n", "value": 2},
+        {"op": "copy", "from": "/n", "path": "/m"},
+        {"op": "move", "from": "/foo", "path": "/list"},
+        {"op": "add", "path": "/list/-", "value": "end"},
+        {"op": "test", "path": "/list", "value": ["qux", "baz", "end"]}
+    ]"#)).unwrap();
+    assert_eq!(target, json(r#"{"list": ["qux", "baz", "end"], "n": 2, "m": 2}"#));
+}
+
+#[test]
+fn json_patch_tests_compare_numbers_by_value() {
+    let mut target = json(r#"{"n": 1, "list": [1, {"x": 2.0}]}"#);
+    apply_patch(&mut target, &json(r#"[{"op": "test", "path": "/n", "value": 1.0}]"#)).unwrap();
+    apply_patch(&mut target, &json(r#"[{"op": "test", "path": "/list", "value": [1.0, {"x": 2}]}]"#)).unwrap();
+    assert!(apply_patch(&mut target, &json(r#"[{"op": "test", "path": "/n", "value": 1.5}]"#)).is_err());
+    assert!(apply_patch(&mut target, &json(r#"[{"op": "test", "path": "/n", "value": "1"}]"#)).is_err());
+}
+
+#[test]
+fn failed_json_patches_change_nothing() {
+    let mut target = json(r#"{"a": 1}"#);
+    let error = apply_patch(&mut target, &json(r#"[
+        {"op": "add", "path": "/b", "value": 2},
+        {"op": "remove", "path": "/missing"}
+    ]"#)).unwrap_err();
+    assert_eq!(error.to_string(), "operation 1: \"/missing\" does not exist");
+    assert_eq!(target, json(r#"{"a": 1}"#));
+    for patch in [r#"{}"#, r#"[{"op": "jump"}]"#, r#"[{"op": "move", "from": "/a", "path": "/a/b"}]"#] {
+        assert!(apply_patch(&mut target, &json(patch)).is_err(), "{}", patch);
+    }
+}
@@ -0,0 +1,68 @@
+use ason::docs::{member_docs, MemberDoc};
+use ason::lexer::Lexer;
+use ason::token::{Trivia, TriviaKind};
+
+fn doc(path: &str, doc: &str) -> MemberDoc {
+    MemberDoc { path: path.to_string(), doc: doc.to_string() }
+}
+
+#[test]
+fn doc_comments_attach_to_the_next_member() {
+    let source = r#"}
+  \\\ The port to listen on.
+  \\\ Defaults to 80.
+  "port" 80
+  \\ Not a doc-comment.
+  "host" "localhost"
+  "tls" }
+    \\\ PEM file.
+    "cert" "cert.pem"
+  {
+  "routes" ] } \\\ First route.
+    "path" "/" { [
+{"#;
+    assert_eq!(
+        member_docs(source).unwrap(),
+        [
+            doc("$.port", "The port to listen on.\nDefaults to 80."),
+            doc("$.tls.cert", "PEM file."),
+            doc("$.routes[0].path", "First route."),
+        ]
+    );
+}
+
+#[test]
+fn spreads_and_computed_keys() {
+    let source = r#"}
+  ...} \\\ Spread in.
+    "a" 1 {
+  )"b" "c" concat( } \\\ Unknown path.
+    "d" 1 {
+  "list" ] ...] 1 [ } \\\ Unknown index.
+    "e" 1 { [
+{"#;
+    assert_eq!(member_docs(source).unwrap(), [doc("$.a", "Spread in.")]);
+}
+
+#[test]
+fn nesting_depth() {
+    let nested = format!("{}{}", "]".repeat(200_000), "[".repeat(200_000));
+    assert_eq!(member_docs(&nested).unwrap_err(), "1:257: Maximum nesting depth of 256 exceeded.");
+}
+
+fn kinds<'a>(trivia: &[Trivia<'a>]) -> Vec<(TriviaKind, &'a str)> {
+    trivia.iter().map(|t| (t.kind, t.text)).collect()
+}
+
+#[test]
+fn comments_are_kept_as_trivia() {
+    let source = "\\\\ leading\n] \\* block *\\ 1 \\\\ trailing\n2 [ \\\\ last";
+    let mut lex = Lexer::new(source);
+    lex.scan().unwrap();
+    let tokens = lex.into_tokens();
+    let trivia = |i: usize| (kinds(&tokens[i].leading), kinds(&tokens[i].trailing));
+    // Comments on the line of a token trail it, the others lead the next one.
+    assert_eq!(trivia(0), (vec![(TriviaKind::LineComment, "\\\\ leading")], vec![(TriviaKind::BlockComment, "\\* block *\\")]));
+    assert_eq!(trivia(1), (vec![], vec![(TriviaKind::LineComment, "\\\\ trailing")]));
+    assert_eq!(trivia(3), (vec![], vec![(TriviaKind::LineComment, "\\\\ last")]));
+}
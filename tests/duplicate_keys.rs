@@ -0,0 +1,61 @@
+use ason::lexer::Lexer;
+use ason::parser::{DuplicateKeys, Parser};
+use ason::{AsonValue, Environment};
+
+const SOURCE: &str = "}\n  \"a\" 1\n  \"b\" 2\n  \"a\" 3\n{";
+
+fn evaluate(source: &str, policy: DuplicateKeys) -> (Result<AsonValue, String>, Vec<String>) {
+    let mut env = Environment::new();
+    env.duplicate_keys = policy;
+    let result = AsonValue::from_ason_string_in(source, &mut env).map_err(|e| e.to_string());
+    (result, env.warnings.iter().map(|w| w.to_string()).collect())
+}
+
+fn object(members: &[(&str, i64)]) -> AsonValue {
+    AsonValue::Object(members.iter().map(|(k, v)| (k.to_string(), (*v).into())).collect())
+}
+
+#[test]
+fn policies() {
+    let (result, warnings) = evaluate(SOURCE, DuplicateKeys::Warn);
+    assert_eq!(result.unwrap(), object(&[("a", 3), ("b", 2)]));
+    assert_eq!(warnings, ["<string>:4:3: Duplicate key \"a\", first defined at 2:3."]);
+
+    let (result, warnings) = evaluate(SOURCE, DuplicateKeys::Error);
+    assert_eq!(result.unwrap_err(), "<string>:4:3: Duplicate key \"a\", first defined at 2:3.");
+    assert!(warnings.is_empty());
+
+    let (result, warnings) = evaluate(SOURCE, DuplicateKeys::LastWins);
+    assert_eq!(result.unwrap(), object(&[("a", 3), ("b", 2)]));
+    assert!(warnings.is_empty());
+
+    let (result, warnings) = evaluate(SOURCE, DuplicateKeys::FirstWins);
+    assert_eq!(result.unwrap(), object(&[("a", 1), ("b", 2)]));
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn only_keys_of_the_same_object_clash() {
+    let source = "} \"a\" } \"a\" 1 { \"b\" } \"a\" 2 { {";
+    let (result, warnings) = evaluate(source, DuplicateKeys::Error);
+    assert!(result.is_ok());
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn computed_keys_clash_with_literal_ones() {
+    let source = "} \"ab\" 1 )\"a\" \"b\" concat( 2 {";
+    let (result, _) = evaluate(source, DuplicateKeys::Error);
+    assert_eq!(result.unwrap_err(), "<string>:1:10: Duplicate key \"ab\", first defined at 1:3.");
+}
+
+#[test]
+fn parser_policy_overrides_the_environment() {
+    let mut lex = Lexer::new(SOURCE);
+    lex.scan().unwrap();
+    let tokens = lex.into_tokens();
+    let mut env = Environment::new();
+    env.duplicate_keys = DuplicateKeys::Error;
+    let value = Parser::new(&tokens, "<string>".into(), &mut env).duplicate_keys(DuplicateKeys::FirstWins).parse().unwrap();
+    assert_eq!(value, object(&[("a", 1), ("b", 2)]));
+}
@@ -0,0 +1,78 @@
+use ason::emit::{emitter, FORMATS};
+use ason::AsonValue;
+
+fn json(s: &str) -> AsonValue {
+    AsonValue::from_json_string(s).unwrap()
+}
+
+fn emit(format: &str, value: &AsonValue) -> Result<String, String> {
+    emitter(format).unwrap().emit(value).map_err(|e| e.to_string())
+}
+
+#[test]
+fn every_format_has_an_emitter() {
+    for format in FORMATS {
+        assert!(emitter(format).is_some(), "{}", format);
+    }
+    assert!(emitter("xml").is_none());
+}
+
+#[test]
+fn yaml() {
+    let value = json(r#"{"name": "api", "port": 80, "ratio": 1.0, "tags": ["a", "true", {"b": null}], "empty": [], "env": {}, "note": "a: b"}"#);
+    let expected = "\
+empty: []
+env: {}
+name: api
+note: \"a: b\"
+port: 80
+ratio: 1.0
+tags:
+  - a
+  - \"true\"
+  - b: null";
+    assert_eq!(emit("yaml", &value).unwrap(), expected);
+    assert_eq!(emit("yaml", &json(r#"[[1, 2], {"a": {"b": 1}}]"#)).unwrap(), "- - 1\n  - 2\n- a:\n    b: 1");
+}
+
+#[test]
+fn toml() {
+    let value = json(r#"{"title": "x", "server": {"port": 80, "hosts": ["a", "b"]}, "routes": [{"path": "/"}, {"path": "/b"}], "key with space": 1.5}"#);
+    let expected = "\
+\"key with space\" = 1.5
+title = \"x\"
+
+[[routes]]
+path = \"/\"
+
+[[routes]]
+path = \"/b\"
+
+[server]
+hosts = [\"a\", \"b\"]
+port = 80";
+    assert_eq!(emit("toml", &value).unwrap(), expected);
+}
+
+#[test]
+fn toml_rejects_what_it_cannot_write() {
+    assert_eq!(emit("toml", &json(r#"{"a": {"b": [1, null]}}"#)).unwrap_err(), "null at $.a.b[1] can't be written as TOML");
+    assert_eq!(emit("toml", &json("[1]")).unwrap_err(), "TOML documents must be objects, got array");
+}
+
+#[test]
+fn canonical_json() {
+    let value = json(r#"{"b": [1e21, 1e-7, 0.000001, 100, -0.5], "a": "\u00e9\n", "\u00e9": true, "\ud83d\ude00": null}"#);
+    assert_eq!(
+        emit("canonical-json", &value).unwrap(),
+        "{\"a\":\"é\\n\",\"b\":[1e+21,1e-7,0.000001,100,-0.5],\"é\":true,\"😀\":null}"
+    );
+    assert_eq!(emit("canonical-json", &json("9007199254740993")).unwrap_err(), "9007199254740993 at $ can't be written exactly as canonical JSON");
+}
+
+#[test]
+fn ason_round_trips() {
+    let value = json(r#"{"a": [1, -2, 2.5, "x\\y", null, true], "b": {}}"#);
+    let ason = emit("ason", &value).unwrap();
+    assert_eq!(AsonValue::from_ason_string(&ason).unwrap(), value);
+}
@@ -0,0 +1,84 @@
+use std::path::{Path, PathBuf};
+
+use ason::{AsonValue, Environment};
+
+/// A directory of its own for each test, holding `files`.
+fn directory(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ason-include-{}-{}", std::process::id(), name));
+    for (path, content) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+fn evaluate(path: &Path) -> Result<AsonValue, String> {
+    let source = std::fs::read_to_string(path).unwrap();
+    AsonValue::from_ason_file_in(&source, path, &mut Environment::new()).map_err(|e| e.msg)
+}
+
+#[test]
+fn include_is_relative_to_the_including_file() {
+    let dir = directory("relative", &[
+        ("main.ason", "} \"common\" )\"lib/common.ason\" include( {"),
+        ("lib/common.ason", "} \"port\" 80 \"host\" )\"host.ason\" include( {"),
+        ("lib/host.ason", "\"localhost\""),
+    ]);
+    let value = evaluate(&dir.join("main.ason")).unwrap();
+    assert_eq!(value.pointer("/common/port"), Some(&AsonValue::from(80)));
+    assert_eq!(value.pointer("/common/host"), Some(&AsonValue::from("localhost")));
+}
+
+#[test]
+fn import_brings_bindings_into_scope() {
+    let dir = directory("import", &[
+        ("main.ason", "]\n)\"vars.ason\" import(\nregion )replicas 1 +(\n["),
+        ("vars.ason", "])\"eu\" \"region\" defvar( )2 \"replicas\" defvar([")
+    ]);
+    assert_eq!(evaluate(&dir.join("main.ason")).unwrap(), AsonValue::from(vec![
+        AsonValue::Null,
+        AsonValue::from("eu"),
+        AsonValue::from(3),
+    ]));
+}
+
+#[test]
+fn included_files_do_not_see_the_caller() {
+    let dir = directory("isolated", &[
+        ("main.ason", "] )1 \"sym\" defvar( )\"sym.ason\" include( [" ),
+        ("twice.ason", "] )1 \"sym\" defvar( )\"sym.ason\" include( )2 \"sym\" defvar( )\"sym.ason\" include( ["),
+        ("sym.ason", "sym"),
+    ]);
+    let error = evaluate(&dir.join("main.ason")).unwrap_err();
+    assert!(error.contains("Undefined symbol: sym"), "{}", error);
+    assert!(evaluate(&dir.join("twice.ason")).is_err());
+}
+
+#[test]
+fn included_files_are_evaluated_once() {
+    let dir = directory("cached", &[
+        ("main.ason", "] )\"shared.ason\" include( )\"shared.ason\" include( ["),
+        ("shared.ason", "1"),
+    ]);
+    let mut env = Environment::new();
+    let path = dir.join("main.ason");
+    let source = std::fs::read_to_string(&path).unwrap();
+    let value = AsonValue::from_ason_file_in(&source, &path, &mut env).unwrap();
+    assert_eq!(value[0], value[1]);
+}
+
+#[test]
+fn cycles_and_bad_arguments_are_errors() {
+    let dir = directory("cycle", &[
+        ("a.ason", ")\"b.ason\" include("),
+        ("b.ason", ")\"a.ason\" include("),
+        ("number.ason", ")1 include("),
+        ("import.ason", ")null import("),
+        ("missing.ason", ")\"nowhere.ason\" include("),
+    ]);
+    assert!(evaluate(&dir.join("a.ason")).unwrap_err().contains("Include cycle"));
+    assert_eq!(evaluate(&dir.join("number.ason")).unwrap_err(), "include expects a string, got integer.");
+    assert_eq!(evaluate(&dir.join("import.ason")).unwrap_err(), "import expects a string, got null.");
+    assert!(evaluate(&dir.join("missing.ason")).unwrap_err().starts_with("IO error: "));
+}
@@ -0,0 +1,34 @@
+use ason::io::MemoryIo;
+use ason::{AsonValue, Environment};
+
+fn environment(io: &MemoryIo) -> Environment {
+    let mut env = Environment::new();
+    env.io = Box::new(io.clone());
+    env
+}
+
+#[test]
+fn env_reads_through_the_environment_io() {
+    let io = MemoryIo::new();
+    io.set_var("REGION", "us-east-1");
+    let mut env = environment(&io);
+    let value = AsonValue::from_ason_string_in("])\"REGION\" env( )\"ZONE\" \"a\" env([", &mut env).unwrap();
+    assert_eq!(value, AsonValue::from(vec!["us-east-1", "a"]));
+}
+
+#[test]
+fn env_errors() {
+    let mut env = environment(&MemoryIo::new());
+    let error = AsonValue::from_ason_string_in(")\"ZONE\" env(", &mut env).unwrap_err();
+    assert_eq!(error.msg, "Environment variable 'ZONE' is not set.");
+    let error = AsonValue::from_ason_string_in(")1 env(", &mut env).unwrap_err();
+    assert_eq!(error.msg, "env expects a string, got integer.");
+}
+
+#[test]
+fn constants_are_symbols() {
+    let mut env = Environment::new();
+    env.add_constant("replicas".into(), AsonValue::from(3));
+    let value = AsonValue::from_ason_string_in("} \"replicas\" )replicas 1 +( {", &mut env).unwrap();
+    assert_eq!(value["replicas"], AsonValue::from(4));
+}
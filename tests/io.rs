@@ -0,0 +1,81 @@
+use std::path::Path;
+
+use ason::io::MemoryIo;
+use ason::limits::Limits;
+use ason::{AsonValue, Environment};
+
+fn environment(io: &MemoryIo, limits: Limits) -> Environment {
+    let mut env = Environment::with_limits(limits);
+    env.io = Box::new(io.clone());
+    env
+}
+
+fn evaluate(io: &MemoryIo, path: &str, limits: Limits) -> Result<AsonValue, String> {
+    let mut env = environment(io, limits);
+    let mut source = String::new();
+    if let Ok(s) = env.read_to_string(Path::new(path)) {
+        source = s;
+    }
+    AsonValue::from_ason_file_in(&source, Path::new(path), &mut env).map_err(|e| e.msg)
+}
+
+#[test]
+fn output_is_captured() {
+    let io = MemoryIo::new();
+    let mut env = environment(&io, Limits::default());
+    AsonValue::from_ason_string_in("])\"out\" 1 write-line( )\"err\" write-error([", &mut env).unwrap();
+    assert_eq!(io.stdout(), "\"out\" 1 \n");
+    assert_eq!(io.stderr(), "\"err\" \n");
+}
+
+#[test]
+fn files_are_read_from_memory() {
+    let io = MemoryIo::new();
+    io.add_file("/app/main.ason", "} \"text\" )\"notes.txt\" read-file-to-string( \"common\" )\"common.ason\" include( {");
+    // read-file-to-string reads relative to the working directory.
+    io.add_file("notes.txt", "hello");
+    io.add_file("/app/common.ason", "} \"port\" 80 {");
+    let value = evaluate(&io, "/app/main.ason", Limits::default()).unwrap();
+    assert_eq!(value["text"], AsonValue::from("hello"));
+    assert_eq!(value.pointer("/common/port"), Some(&AsonValue::from(80)));
+}
+
+#[test]
+fn included_files_are_evaluated_once() {
+    let io = MemoryIo::new();
+    io.add_file("/app/main.ason", "] )\"shared.ason\" include( )\"shared.ason\" include( [");
+    io.add_file("/app/shared.ason", "] )\"evaluated\" write-line( 1 [");
+    let value = evaluate(&io, "/app/main.ason", Limits::default()).unwrap();
+    assert_eq!(value[0], value[1]);
+    assert_eq!(io.stdout(), "\"evaluated\" \n");
+}
+
+#[test]
+fn missing_files() {
+    let io = MemoryIo::new();
+    io.add_file("/app/main.ason", ")\"missing.txt\" read-file-to-string(");
+    let error = evaluate(&io, "/app/main.ason", Limits::default()).unwrap_err();
+    assert!(error.starts_with("IO error: missing.txt"), "{}", error);
+}
+
+#[test]
+fn file_read_limit() {
+    let limits = Limits { max_file_read_bytes: 2, ..Limits::default() };
+    let io = MemoryIo::new();
+    io.add_file("/two.txt", "ab");
+    io.add_file("/three.txt", "abc");
+    // Two bytes of a four byte file end on a character boundary.
+    io.add_file("accents.txt", "éé");
+    let mut env = environment(&io, limits);
+    assert_eq!(env.read_to_string(Path::new("/two.txt")).unwrap(), "ab");
+    for path in ["/three.txt", "accents.txt"] {
+        let error = env.read_to_string(Path::new(path)).unwrap_err();
+        assert_eq!(error.to_string(), "File larger than 2 bytes.");
+    }
+
+    io.add_file("/main.ason", ")\"accents.txt\" read-file-to-string(");
+    let limits = Limits { max_file_read_bytes: 40, ..Limits::default() };
+    assert_eq!(evaluate(&io, "/main.ason", limits.clone()).unwrap(), AsonValue::from("éé"));
+    io.add_file("accents.txt", "é".repeat(21));
+    assert_eq!(evaluate(&io, "/main.ason", limits.clone()).unwrap_err(), "File larger than 40 bytes.");
+}
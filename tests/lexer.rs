@@ -0,0 +1,63 @@
+use ason::lexer::Lexer;
+use ason::token::TokenKind;
+
+fn string(source: &str) -> Result<String, (String, usize, usize)> {
+    let mut lexer = Lexer::new(source);
+    if let Err(msg) = lexer.scan() {
+        return Err((msg, lexer.line(), lexer.column()));
+    }
+    match lexer.into_tokens().remove(0).kind {
+        TokenKind::StringLiteral(s) => Ok(s),
+        kind => panic!("expected a string, got {:?}", kind),
+    }
+}
+
+#[test]
+fn escapes() {
+    for (source, expected) in [
+        (r#""\"""#, "\""),
+        (r#""\\""#, "\\"),
+        (r#""\/""#, "/"),
+        (r#""\n""#, "\n"),
+        (r#""\r""#, "\r"),
+        (r#""\t""#, "\t"),
+        (r#""\b""#, "\u{8}"),
+        (r#""\f""#, "\u{c}"),
+        (r#""\0""#, "\0"),
+        (r#""\u00e9""#, "é"),
+        (r#""\uD83D\uDE00""#, "😀"),
+        (r#""\u{1F600}""#, "😀"),
+    ] {
+        assert_eq!(string(source).unwrap(), expected, "{}", source);
+    }
+}
+
+#[test]
+fn slashes_are_literal() {
+    assert_eq!(string(r#""sub/b.ason""#).unwrap(), "sub/b.ason");
+    assert_eq!(string(r#""/usr/bin/env""#).unwrap(), "/usr/bin/env");
+    assert_eq!(string(r#""a//b""#).unwrap(), "a//b");
+}
+
+#[test]
+fn invalid_escapes() {
+    assert_eq!(string("\"ab\\c\"").unwrap_err(), ("Invalid escape sequence '\\c'".to_string(), 1, 4));
+    assert_eq!(string("\"\\u12\"").unwrap_err().0, "Expected 4 hex digits after '\\u'");
+    assert_eq!(string("\"\\u{}\"").unwrap_err().0, "Expected 1 to 6 hex digits in '\\u{...}'");
+    assert_eq!(string("\"\\u{D800}\"").unwrap_err().0, "Invalid unicode scalar value '\\u{D800}'");
+    assert_eq!(string("\"\\uD83D\"").unwrap_err().0, "Unpaired surrogate '\\uD83D'");
+    assert_eq!(string("\"\\uD83Dx\"").unwrap_err().0, "Unpaired surrogate '\\uD83D'");
+    assert_eq!(string("\"\\").unwrap_err().0, "Escape at end of string");
+}
+
+#[test]
+fn raw_strings() {
+    assert_eq!(string("\"\"\"\nno \\escapes \"here\"\"\"\"").unwrap(), "no \\escapes \"here\"");
+    assert_eq!(string("\"\"\"\"a \"\"\" b\"\"\"\"").unwrap(), "a \"\"\" b");
+}
+
+#[test]
+fn strings_round_trip() {
+    let value = ason::AsonValue::String("a\\b \"c\" /d\n\u{1}".to_string());
+    assert_eq!(ason::AsonValue::from_ason_string(&value.to_ason()).unwrap(), value);
+}
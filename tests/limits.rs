@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use ason::lexer::Lexer;
+use ason::limits::Limits;
+use ason::parser::Parser;
+use ason::{AsonValue, Environment};
+
+fn evaluate(source: &str, limits: Limits) -> Result<AsonValue, String> {
+    AsonValue::from_ason_string_in(source, &mut Environment::with_limits(limits)).map_err(|e| e.msg)
+}
+
+#[test]
+fn nesting_depth() {
+    let limits = Limits { max_depth: 3, ..Limits::default() };
+    assert!(evaluate("]]][[[", limits.clone()).is_ok());
+    assert_eq!(evaluate("]]]][[[[", limits.clone()).unwrap_err(), "Maximum nesting depth of 3 exceeded.");
+    assert_eq!(evaluate("))))1 2 +( 3 +( 4 +( 5 +(", limits).unwrap_err(), "Maximum nesting depth of 3 exceeded.");
+    // Far deeper than the stack allows without the limit.
+    let nested = format!("{}{}", "]".repeat(200_000), "[".repeat(200_000));
+    assert_eq!(evaluate(&nested, Limits::default()).unwrap_err(), "Maximum nesting depth of 256 exceeded.");
+}
+
+#[test]
+fn evaluation_steps() {
+    let limits = Limits { max_steps: 4, ..Limits::default() };
+    assert!(evaluate(")1 2 +(", limits.clone()).is_ok());
+    assert_eq!(evaluate(")1 2 3 4 +(", limits).unwrap_err(), "Evaluation step budget of 4 exhausted.");
+}
+
+#[test]
+fn string_and_array_sizes() {
+    let limits = Limits { max_string_size: 4, max_array_size: 2, ..Limits::default() };
+    assert!(evaluate("\"abcd\"", limits.clone()).is_ok());
+    assert_eq!(evaluate("\"abcde\"", limits.clone()).unwrap_err(), "String longer than 4 bytes.");
+    assert_eq!(evaluate(")\"abc\" \"de\" concat(", limits.clone()).unwrap_err(), "String longer than 4 bytes.");
+    assert_eq!(evaluate("]1 2 3[", limits).unwrap_err(), "Array longer than 2 elements.");
+}
+
+#[test]
+fn timeout() {
+    let limits = Limits { timeout: Some(Duration::ZERO), ..Limits::default() };
+    assert_eq!(evaluate(")1 2 +(", limits).unwrap_err(), "Evaluation took longer than 0ms.");
+}
+
+#[test]
+fn builtins_fail_without_panicking() {
+    for (source, error) in [
+        (")1 \"a\" +(", "+ expects numbers, got string."),
+        (")2 \"a\" /(", "/ expects numbers, got string."),
+        (")1 read-file-to-string(", "read-file-to-string expects a string, got integer."),
+        (")1 2 defvar(", "defvar expects a string, got integer."),
+        (")4611686018427387904 2 *(", "Integer overflow in *."),
+    ] {
+        assert_eq!(evaluate(source, Limits::default()).unwrap_err(), error, "{}", source);
+    }
+}
+
+#[test]
+fn errors_leave_the_nesting_depth_balanced() {
+    // Parsers don't reset the budget, so a leaked level would add up.
+    let mut env = Environment::new();
+    for source in ["]]] undefined [[[", "}}} \"a\" ))1 \"b\" +( 2 +( {{{", ")))1 2 +( 3 +(", "]]] 1"] {
+        let mut lex = Lexer::new(source);
+        lex.scan().unwrap();
+        let tokens = lex.into_tokens();
+        for _ in 0..100 {
+            assert!(Parser::new(&tokens, "<string>".into(), &mut env).parse().is_err(), "{}", source);
+        }
+    }
+    let mut lex = Lexer::new("]]]1[[[");
+    lex.scan().unwrap();
+    let tokens = lex.into_tokens();
+    assert!(Parser::new(&tokens, "<string>".into(), &mut env).parse().is_ok());
+}
+
+#[test]
+fn json_nesting_depth() {
+    let fits = format!("{}{}", "[".repeat(256), "]".repeat(256));
+    assert!(AsonValue::from_json_string(&fits).is_ok());
+    let nested = format!("{}{}", "[".repeat(200_000), "]".repeat(200_000));
+    assert_eq!(AsonValue::from_json_string(&nested).unwrap_err().to_string(), "1:258: Nesting deeper than 256 levels.");
+    let objects = "{\"a\": ".repeat(200_000);
+    assert!(AsonValue::from_json_string(&objects).is_err());
+}
@@ -0,0 +1,64 @@
+use ason::lint::{lint, Level, LintConfig};
+
+/// The findings for `source` as `line:column rule`.
+fn findings(source: &str, config: &LintConfig) -> Vec<String> {
+    lint(source, "test.ason", config)
+        .unwrap()
+        .iter()
+        .map(|l| format!("{}:{} {}", l.error.line, l.error.column, l.rule))
+        .collect()
+}
+
+#[test]
+fn rules() {
+    let config = LintConfig::default();
+    let cases = [
+        ("] )1 \"x\" defvar( [", vec!["1:6 unused-defvar"]),
+        ("] )1 \"x\" defvar( x [", vec![]),
+        ("] )1 \"x\" defvar( )2 \"x\" defvar( x [", vec!["1:21 shadowing"]),
+        ("] )1 \"concat\" defvar( concat [", vec!["1:6 shadowing"]),
+        ("} \"a\": 1 \"b\" 2 {", vec!["1:1 mixed-separators"]),
+        ("} \"a\" 1, \"b\" 2 \"c\" 3 {", vec!["1:1 mixed-separators"]),
+        ("} \"a\": 1, \"b\": 2, {", vec![]),
+        ("] )60 60 24 *( [", vec!["1:3 constant-expression"]),
+        ("] )))1 2 +( 3 *( 4 -( [", vec!["1:3 constant-expression"]),
+        ("] )\"hi\" write-line( [", vec!["1:9 side-effect"]),
+        ("] )x 0 /( [", vec!["1:6 division-by-zero"]),
+        ("] )1 0.0 /( [", vec!["1:6 division-by-zero"]),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(findings(source, &config), expected, "{}", source);
+    }
+}
+
+#[test]
+fn computed_keys_and_spreads_are_checked() {
+    let source = "} )1 2 +( 1 ...} \"a\" )1 0 /( { {";
+    assert_eq!(findings(source, &LintConfig::default()), ["1:3 constant-expression", "1:25 division-by-zero"]);
+}
+
+#[test]
+fn levels() {
+    let source = "] )1 \"x\" defvar( )x 0 /( [";
+    let mut config = LintConfig::default();
+    config.set("unused-defvar", Level::Off).unwrap();
+    config.set("division-by-zero", Level::Error).unwrap();
+    let lints = lint(source, "test.ason", &config).unwrap();
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].level, Level::Error);
+    assert_eq!(lints[0].to_string(), "test.ason:1:21: Division by zero. [division-by-zero]");
+    assert_eq!(config.set("no-such-rule", Level::Off).unwrap_err(), "unknown lint rule 'no-such-rule'");
+}
+
+#[test]
+fn nesting_depth() {
+    let config = LintConfig::default();
+    let arrays = format!("{}{}", "]".repeat(200_000), "[".repeat(200_000));
+    assert_eq!(lint(&arrays, "test.ason", &config).unwrap_err().to_string(), "1:257: Maximum nesting depth of 256 exceeded.");
+    let exprs = format!("{}1{}", ")".repeat(200_000), " 1 +(".repeat(200_000));
+    assert_eq!(lint(&exprs, "test.ason", &config).unwrap_err().to_string(), "1:257: Maximum nesting depth of 256 exceeded.");
+    let inside = format!("] {}1{} [", ")".repeat(256), " 1 +(".repeat(256));
+    assert!(lint(&inside, "test.ason", &config).is_err());
+    let fits = format!("] {}1{} [", ")".repeat(255), " 1 +(".repeat(255));
+    assert_eq!(findings(&fits, &config), ["1:3 constant-expression"]);
+}
@@ -0,0 +1,96 @@
+use ason::AsonValue;
+
+/// Opens `text` as `file:///test.ason`, sends `requests` as `(method, params)`
+/// about it, and returns every message the server wrote.
+fn session(text: &str, requests: &[(&str, &str)]) -> Vec<AsonValue> {
+    let uri = "\"file:///test.ason\"";
+    let text = AsonValue::String(text.to_string()).to_json();
+    let mut messages = vec![format!(
+        r#"{{"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {{"textDocument": {{"uri": {}, "text": {}}}}}}}"#,
+        uri, text
+    )];
+    for (id, (method, params)) in requests.iter().enumerate() {
+        messages.push(format!(
+            r#"{{"jsonrpc": "2.0", "id": {}, "method": "{}", "params": {{"textDocument": {{"uri": {}}}{}}}}}"#,
+            id, method, uri, params
+        ));
+    }
+    messages.push(r#"{"jsonrpc": "2.0", "method": "exit"}"#.to_string());
+    let input: String = messages.iter().map(|m| format!("Content-Length: {}\r\n\r\n{}", m.len(), m)).collect();
+
+    let mut output = Vec::new();
+    ason::lsp::run(input.as_bytes(), &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    output
+        .split("Content-Length: ")
+        .skip(1)
+        .map(|m| AsonValue::from_json_string(m.split_once("\r\n\r\n").unwrap().1).unwrap())
+        .collect()
+}
+
+fn names(symbols: &AsonValue) -> Vec<String> {
+    symbols.as_array().unwrap().iter().map(|s| s["name"].as_str().unwrap().to_string()).collect()
+}
+
+#[test]
+fn document_symbols() {
+    let text = r#"}
+  \\\ Where to listen
+  "server" } "port" 80 "hosts" ] } "name" "a" { [ {
+  ...} "extra" true {
+  )"computed" concat( 1
+{"#;
+    let replies = session(text, &[("textDocument/documentSymbol", "")]);
+    let symbols = &replies[1]["result"];
+    assert_eq!(names(symbols), ["server", "extra", ")\"computed\" concat("]);
+    let server = &symbols[0];
+    assert_eq!(server["detail"].as_str(), Some("Where to listen"));
+    assert_eq!(server["kind"].as_i64(), Some(19));
+    assert_eq!(server["selectionRange"]["start"]["line"].as_i64(), Some(2));
+    assert_eq!(server["selectionRange"]["start"]["character"].as_i64(), Some(2));
+    assert_eq!(names(&server["children"]), ["port", "hosts"]);
+    assert_eq!(names(&server["children"][1]["children"]), ["0"]);
+    assert_eq!(names(&server["children"][1]["children"][0]["children"]), ["name"]);
+}
+
+#[test]
+fn document_symbols_of_deep_or_broken_documents() {
+    let deep = format!("{}{}", "]".repeat(300), "[".repeat(300));
+    let replies = session(&deep, &[("textDocument/documentSymbol", "")]);
+    assert_eq!(replies[1]["result"], AsonValue::Array(vec![]));
+
+    let replies = session("} \"a\" 1", &[("textDocument/documentSymbol", "")]);
+    assert_eq!(replies[1]["result"], AsonValue::Array(vec![]));
+}
+
+#[test]
+fn diagnostics_hover_and_definition() {
+    let text = "] )1 \"x\" defvar( )x 2 +( [";
+    let replies = session(
+        text,
+        &[
+            ("textDocument/hover", r#", "position": {"line": 0, "character": 21}"#),
+            ("textDocument/definition", r#", "position": {"line": 0, "character": 18}"#),
+            ("textDocument/completion", r#", "position": {"line": 0, "character": 0}"#),
+        ],
+    );
+    assert_eq!(replies[0]["method"].as_str(), Some("textDocument/publishDiagnostics"));
+    assert_eq!(replies[0]["params"]["diagnostics"], AsonValue::Array(vec![]));
+    assert!(replies[1]["result"]["contents"]["value"].as_str().unwrap().contains('3'));
+    assert_eq!(replies[2]["result"]["range"]["start"]["character"].as_i64(), Some(5));
+    let completions = replies[3]["result"].as_array().unwrap();
+    assert!(completions.iter().any(|c| c["label"].as_str() == Some("concat")));
+
+    let replies = session("] 1 )2 \"a\" +( [", &[]);
+    let diagnostics = replies[0]["params"]["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["range"]["start"]["character"].as_i64(), Some(4));
+}
+
+#[test]
+fn deep_messages_are_errors() {
+    let body = "[".repeat(200_000);
+    let input = format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+    let error = ason::lsp::run(input.as_bytes(), Vec::new()).unwrap_err();
+    assert!(error.to_string().contains("Nesting deeper than 256 levels."), "{}", error);
+}
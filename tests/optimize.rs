@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use ason::optimize::partially_evaluate;
+use ason::AsonValue;
+
+fn simplify(source: &str, known: &[(&str, AsonValue)]) -> String {
+    let known: HashMap<String, AsonValue> = known.iter().map(|(k, v)| (k.to_string(), v.clone())).collect();
+    partially_evaluate(source, known).unwrap()
+}
+
+#[test]
+fn folds_pure_builtins() {
+    let source = "} \\\\ A day\n  \"ttl\" )60 60 24 *(\n  \"name\" )\"svc-\" \"api\" concat(\n{";
+    assert_eq!(simplify(source, &[]), "} \\\\ A day\n  \"ttl\" 86400\n  \"name\" \"svc-api\"\n{");
+    assert_eq!(simplify("] )0 5 -( )1 0 /( )9223372036854775807 1 +( [", &[]), "] )0 5 -( )1 0 /( )9223372036854775807 1 +( [");
+    assert_eq!(simplify("] )1 \"a\" +( )\"hi\" write-line( [", &[]), "] )1 \"a\" +( )\"hi\" write-line( [");
+}
+
+#[test]
+fn inlines_constants_and_inputs() {
+    let source = "] )60 \"minute\" defvar( )minute 2 *( region )\"eu-\" region concat( zone [";
+    assert_eq!(
+        simplify(source, &[("region", "west".into())]),
+        "] )60 \"minute\" defvar( 120 \"west\" \"eu-west\" zone ["
+    );
+    // A binding only applies after it, and an unknown one forgets its name.
+    let source = "] )x 1 +( )1 \"x\" defvar( )x 1 +( )y \"x\" defvar( )x 1 +( [";
+    assert_eq!(simplify(source, &[]), "] )x 1 +( )1 \"x\" defvar( 2 )y \"x\" defvar( )x 1 +( [");
+    let source = "] )z \"y\" defvar( )y 1 +( )x 1 +( [";
+    assert_eq!(simplify(source, &[("x", 1.into()), ("y", 1.into())]), "] )z \"y\" defvar( )y 1 +( 2 [");
+    // Without a literal name, any symbol may have changed.
+    let source = "] )z name defvar( )x 1 +( [";
+    assert_eq!(simplify(source, &[("x", 1.into())]), "] )z name defvar( )x 1 +( [");
+}
+
+#[test]
+fn keys_and_spreads() {
+    let source = "} )\"a\" \"b\" concat( 1 ...} \"c\" )2 2 +( { {";
+    assert_eq!(simplify(source, &[]), "} \"ab\" 1 ...} \"c\" 4 { {");
+}
+
+#[test]
+fn nesting_depth() {
+    let arrays = format!("{}{}", "]".repeat(200_000), "[".repeat(200_000));
+    let error = partially_evaluate(&arrays, HashMap::new()).unwrap_err();
+    assert_eq!(error.to_string(), "1:257: Maximum nesting depth of 256 exceeded.");
+    let exprs = format!("{}1{}", ")".repeat(200_000), " 1 +(".repeat(200_000));
+    assert!(partially_evaluate(&exprs, HashMap::new()).is_err());
+    let fits = format!("{}1{}", ")".repeat(200), " 1 +(".repeat(200));
+    assert_eq!(simplify(&fits, &[]), "201");
+}
@@ -0,0 +1,82 @@
+use ason::AsonValue;
+
+fn json(s: &str) -> AsonValue {
+    AsonValue::from_json_string(s).unwrap()
+}
+
+fn document() -> AsonValue {
+    json(r#"{"hobbies": ["chess", {"name": "go"}], "a/b": 1, "m~n": 2, "~1": 3, "": 4, "n": null}"#)
+}
+
+#[test]
+fn pointer() {
+    let doc = document();
+    let cases = [
+        ("", Some(doc.clone())),
+        ("/hobbies/0", Some(json(r#""chess""#))),
+        ("/hobbies/1/name", Some(json(r#""go""#))),
+        ("/a~1b", Some(json("1"))),
+        ("/m~0n", Some(json("2"))),
+        ("/~01", Some(json("3"))),
+        ("/", Some(json("4"))),
+        ("/n", Some(json("null"))),
+        ("/hobbies/2", None),
+        ("/hobbies/-", None),
+        ("/hobbies/x", None),
+        ("/hobbies/0/name", None),
+        ("/missing", None),
+        ("hobbies", None),
+    ];
+    for (pointer, expected) in cases {
+        assert_eq!(doc.pointer(pointer).cloned(), expected, "{}", pointer);
+    }
+}
+
+#[test]
+fn pointer_mut() {
+    let mut doc = document();
+    *doc.pointer_mut("/hobbies/1/name").unwrap() = "shogi".into();
+    *doc.pointer_mut("/a~1b").unwrap() = 10.into();
+    assert_eq!(doc.pointer("/hobbies/1"), Some(&json(r#"{"name": "shogi"}"#)));
+    assert_eq!(doc.get("a/b"), Some(&json("10")));
+    assert!(doc.pointer_mut("/hobbies/-").is_none());
+    assert!(doc.pointer_mut("/hobbies/9").is_none());
+    assert!(doc.pointer_mut("/n/x").is_none());
+}
+
+#[test]
+fn accessors() {
+    let doc = document();
+    let hobbies = doc.get("hobbies").unwrap();
+    assert_eq!(hobbies.as_array().map(Vec::len), Some(2));
+    assert_eq!(hobbies.get_index(0).and_then(AsonValue::as_str), Some("chess"));
+    assert!(doc.get("n").unwrap().is_null());
+    assert_eq!(json("1").as_i64(), Some(1));
+    assert_eq!(json("1").as_f64(), Some(1.0));
+    assert_eq!(json("1.5").as_f64(), Some(1.5));
+    assert_eq!(json("true").as_bool(), Some(true));
+
+    // A value of another type is `None`, never a conversion.
+    assert!(hobbies.as_object().is_none());
+    assert!(hobbies.get("0").is_none());
+    assert!(doc.get_index(0).is_none());
+    assert!(json("1.5").as_i64().is_none());
+    assert!(json(r#""1""#).as_i64().is_none());
+    assert!(json(r#""1""#).as_f64().is_none());
+    assert!(json("1").as_str().is_none());
+    assert!(json("1").as_bool().is_none());
+    assert!(json("null").as_number().is_none());
+    assert!(json("[]").get_index(0).is_none());
+}
+
+#[test]
+fn mutable_accessors() {
+    let mut doc = document();
+    doc.get_mut("hobbies").unwrap().as_array_mut().unwrap().push("go".into());
+    *doc.get_mut("hobbies").unwrap().get_index_mut(0).unwrap() = "bridge".into();
+    doc.as_object_mut().unwrap().remove("n");
+    assert_eq!(doc.get("hobbies"), Some(&json(r#"["bridge", {"name": "go"}, "go"]"#)));
+    assert!(doc.get("n").is_none());
+    assert!(doc.get_mut("a/b").unwrap().as_array_mut().is_none());
+    assert!(doc.get_index_mut(0).is_none());
+}
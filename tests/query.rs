@@ -0,0 +1,67 @@
+use ason::query::{query, Query};
+use ason::AsonValue;
+
+fn config() -> AsonValue {
+    AsonValue::from_json_string(r#"{
+        "name": "web",
+        "servers": [
+            {"host": "a", "port": 80},
+            {"host": "b", "port": 8080, "tls": true},
+            {"host": "c", "port": 9000}
+        ],
+        "odd key": {"name": "inner"}
+    }"#).unwrap()
+}
+
+/// Runs `expr` and writes the matches as JSON, sorted since members have no order.
+fn select(expr: &str) -> Vec<String> {
+    let value = config();
+    let mut results: Vec<String> = query(&value, expr).unwrap().iter().map(|v| v.to_json()).collect();
+    results.sort();
+    results
+}
+
+#[test]
+fn members_and_indices() {
+    assert_eq!(select("$.name"), ["\"web\""]);
+    assert_eq!(select("$['odd key'].name"), ["\"inner\""]);
+    assert_eq!(select("$.servers[0].host"), ["\"a\""]);
+    assert_eq!(select("$.servers[-1].port"), ["9000"]);
+    assert!(select("$.servers[3]").is_empty());
+    assert!(select("$.missing.name").is_empty());
+    assert_eq!(select("$").len(), 1);
+}
+
+#[test]
+fn wildcards_and_descendants() {
+    assert_eq!(select("$.servers[*].host"), ["\"a\"", "\"b\"", "\"c\""]);
+    assert_eq!(select("$.servers.*.port"), ["80", "8080", "9000"]);
+    assert_eq!(select("$..name"), ["\"inner\"", "\"web\""]);
+}
+
+#[test]
+fn slices() {
+    assert_eq!(select("$.servers[0:2].host"), ["\"a\"", "\"b\""]);
+    assert_eq!(select("$.servers[::2].host"), ["\"a\"", "\"c\""]);
+    assert_eq!(select("$.servers[-2:].host"), ["\"b\"", "\"c\""]);
+    assert_eq!(select("$.servers[::-1].host"), ["\"a\"", "\"b\"", "\"c\""]);
+    assert_eq!(select("$.servers[1::9223372036854775807].host"), ["\"b\""]);
+    assert_eq!(select("$.servers[1::-9223372036854775807].host"), ["\"b\""]);
+    let value = AsonValue::from_json_string(r#"{"a": [1, 2]}"#).unwrap();
+    assert_eq!(query(&value, "$.a[1::9223372036854775807]").unwrap(), [&AsonValue::from(2)]);
+}
+
+#[test]
+fn filters() {
+    assert_eq!(select("$.servers[?(@.port >= 8080)].host"), ["\"b\"", "\"c\""]);
+    assert_eq!(select("$.servers[?(@.port == 80)].host"), ["\"a\""]);
+    assert_eq!(select("$.servers[?(@.host != 'a')].host"), ["\"b\"", "\"c\""]);
+    assert_eq!(select("$.servers[?(@.tls)].host"), ["\"b\""]);
+}
+
+#[test]
+fn errors() {
+    for expr in ["servers", "$.servers[", "$.servers[?(@.port >)]", "$['open"] {
+        assert!(Query::parse(expr).is_err(), "{}", expr);
+    }
+}
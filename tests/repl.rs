@@ -0,0 +1,37 @@
+use ason::repl::{open_brackets, run};
+use ason::Environment;
+
+/// Feeds `input` to the repl line by line and returns everything it wrote.
+fn session(input: &str) -> String {
+    let mut output = Vec::new();
+    run(&mut Environment::new(), input.as_bytes(), &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn definitions_carry_over() {
+    assert_eq!(session("] )2 \"x\" defvar( [\n)x 3 *(\n"), "ason> [\n  2\n]\nason> 6\nason> \n");
+}
+
+#[test]
+fn multi_line_values() {
+    let expected = "ason> ...> ...> {\n  \"a\": [\n    1,\n    2\n  ]\n}\nason> \n";
+    assert_eq!(session("} \"a\"\n] 1 2 [\n{\n"), expected);
+}
+
+#[test]
+fn errors_do_not_end_the_session() {
+    let expected = "\
+ason> Error <string>:1:1: Undefined Symbol.
+ason> 3
+ason> Error unknown command ':nope', try :help
+ason> ";
+    assert_eq!(session(")undefined 1 +(\n)1 2 +(\n:nope\n:quit\n1\n"), expected);
+}
+
+#[test]
+fn brackets_in_strings_and_comments_are_skipped() {
+    assert_eq!(open_brackets("} \"a\" ] \"[\" \\\\ [\n"), 2);
+    assert_eq!(open_brackets("] \\* [ *\\ ["), 0);
+    assert_eq!(open_brackets("\"\"\"]"), 1);
+}
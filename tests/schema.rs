@@ -0,0 +1,131 @@
+use std::time::{Duration, Instant};
+
+use ason::cst::Document;
+use ason::pattern::Pattern;
+use ason::schema::Schema;
+use ason::span::LineIndex;
+use ason::AsonValue;
+
+fn schema(json: &str) -> Schema {
+    Schema::new(AsonValue::from_json_string(json).unwrap()).unwrap()
+}
+
+/// The violations of `source` against `schema`, as `path: msg`.
+fn violations(schema_json: &str, source: &str) -> Vec<String> {
+    let value = AsonValue::from_ason_string(source).unwrap();
+    schema(schema_json).validate(&value).iter().map(|v| v.to_string()).collect()
+}
+
+const SERVICE: &str = r#"{
+    "type": "object",
+    "required": ["name", "port"],
+    "properties": {
+        "name": {"type": "string", "pattern": "^[a-z][a-z0-9-]*$", "maxLength": 12},
+        "port": {"type": "integer", "minimum": 1, "maximum": 65535},
+        "tags": {"type": "array", "items": {"enum": ["web", "db"]}, "maxItems": 2},
+        "ratio": {"type": "number", "exclusiveMaximum": 1}
+    },
+    "additionalProperties": false
+}"#;
+
+#[test]
+fn valid_documents() {
+    assert!(violations(SERVICE, r#"} "name" "api-1" "port" 8080 "tags" ]"web"[ "ratio" 0.5 {"#).is_empty());
+    assert!(violations(r#"{"type": "integer"}"#, "3.0").is_empty());
+    assert!(violations(r#"{"const": 1}"#, "1.0").is_empty());
+}
+
+#[test]
+fn every_violation_is_reported() {
+    let found = violations(SERVICE, r#"} "name" "Api_service_name" "port" 0 "tags" ]"web" "cache" "db"[ "ratio" 1 "extra" null {"#);
+    assert_eq!(found, [
+        "$.extra: additional member is not allowed",
+        "$.name: string is longer than 12 characters",
+        "$.name: \"Api_service_name\" does not match the pattern '^[a-z][a-z0-9-]*$'",
+        "$.port: 0 is less than the minimum of 1",
+        "$.ratio: 1 must be less than 1",
+        "$.tags: array has more than 2 items",
+        "$.tags[1]: \"cache\" is not one of \"web\", \"db\"",
+    ]);
+    assert_eq!(violations(SERVICE, "} {"), ["$: missing required member \"name\"", "$: missing required member \"port\""]);
+    assert_eq!(violations(SERVICE, "]["), ["$: expected object, got array"]);
+    assert_eq!(violations(r#"{"type": ["string", "null"]}"#, "1"), ["$: expected one of \"string\", \"null\", got integer"]);
+}
+
+#[test]
+fn bad_schemas() {
+    for json in [r#"{"pattern": "(a"}"#, r#"{"pattern": "*"}"#, r#"{"items": 1}"#, "[]"] {
+        assert!(Schema::new(AsonValue::from_json_string(json).unwrap()).is_err(), "{}", json);
+    }
+}
+
+#[test]
+fn violations_point_at_the_value() {
+    let source = "}\n  \"name\" \"api\"\n  \"port\" 0\n  \"tags\" ]\n    \"web\"\n    \"cache\"\n  [\n{";
+    let value = AsonValue::from_ason_string(source).unwrap();
+    let document = Document::parse(source).unwrap();
+    let lines = LineIndex::new(source);
+    let located: Vec<(String, (usize, usize))> = schema(SERVICE)
+        .validate_document(&value, &document)
+        .into_iter()
+        .map(|v| (v.path, lines.line_col(v.span.unwrap().start)))
+        .collect();
+    assert_eq!(located, [("$.port".to_string(), (3, 10)), ("$.tags[1]".to_string(), (6, 5))]);
+}
+
+#[test]
+fn spread_values_point_at_the_closest_written_value() {
+    let source = "}\n  \"name\" \"api\"\n  ...}\"port\" 0{\n{";
+    let value = AsonValue::from_ason_string(source).unwrap();
+    let violations = schema(SERVICE).validate_document(&value, &Document::parse(source).unwrap());
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].span.unwrap().start, 0);
+}
+
+#[test]
+fn patterns() {
+    let cases = [
+        ("abc", "xxabcxx", true),
+        ("^abc$", "abc", true),
+        ("^abc$", "abcd", false),
+        ("^a.c$", "a\nc", false),
+        ("^(ab|cd)+$", "abcdab", true),
+        ("^(ab|cd)+$", "abc", false),
+        ("^a{2,3}$", "aaa", true),
+        ("^a{2,3}$", "aaaa", false),
+        ("^a{2}$", "aa", true),
+        ("^a{2,}$", "aaaaa", true),
+        ("^[a-z0-9_-]+$", "ok_1-2", true),
+        ("^[^0-9]+$", "abc1", false),
+        ("^\\d+\\.\\d+$", "1.25", true),
+        ("^\\w+\\s\\W$", "é_1 !", true),
+        ("^(?:x|)y?$", "", true),
+        ("^(a*)*$", "aaaa", true),
+        ("^(a*)*b$", "aaaa", false),
+        ("a{,", "a{,", true),
+    ];
+    for (pattern, s, expected) in cases {
+        assert_eq!(Pattern::new(pattern).unwrap().is_match(s), Ok(expected), "{} on {:?}", pattern, s);
+    }
+    for pattern in ["(a", "a)", "[a", "+", "a**", "\\", "(a{100}){1000}", &"(".repeat(1000)] {
+        assert!(Pattern::new(pattern).is_err(), "{}", pattern);
+    }
+}
+
+#[test]
+fn patterns_take_linear_time() {
+    let started = Instant::now();
+    let long = "a".repeat(200_000);
+    assert_eq!(Pattern::new("^a*$").unwrap().is_match(&long), Ok(true));
+    let nested = format!("{}b", "a".repeat(30));
+    assert_eq!(Pattern::new("^(a+)+$").unwrap().is_match(&nested), Ok(false));
+    assert_eq!(Pattern::new("^(a|a)*c$").unwrap().is_match(&long), Ok(false));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn patterns_have_a_step_budget() {
+    let pattern = Pattern::new("^(a|aa)*$").unwrap();
+    assert!(pattern.is_match_within(&"a".repeat(1000), 100).is_err());
+    assert_eq!(pattern.is_match_within(&"a".repeat(1000), 1_000_000), Ok(true));
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "serde")]
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use ason::{from_str, to_string};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Protocol {
+    Http,
+    Tcp { port: u16 },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Service {
+    name: String,
+    replicas: u32,
+    ratio: f64,
+    tags: Vec<String>,
+    protocol: Protocol,
+    fallback: Option<String>,
+}
+
+#[test]
+fn structs_round_trip() {
+    let service = Service {
+        name: "api".into(),
+        replicas: 3,
+        ratio: 0.5,
+        tags: vec!["a".into(), "b/c".into()],
+        protocol: Protocol::Tcp { port: 8080 },
+        fallback: None,
+    };
+    let source = to_string(&service).unwrap();
+    assert_eq!(from_str::<Service>(&source).unwrap(), service);
+}
+
+#[test]
+fn reads_documents() {
+    let service: Service = from_str(r#"}
+        "name" )"a" "pi" concat(
+        "replicas" )1 2 +(
+        "ratio" 0.5
+        "tags" ][
+        "protocol" "Http"
+        "fallback" "b"
+    {"#).unwrap();
+    assert_eq!(service.name, "api");
+    assert_eq!(service.replicas, 3);
+    assert_eq!(service.protocol, Protocol::Http);
+    assert_eq!(service.fallback.as_deref(), Some("b"));
+}
+
+#[test]
+fn non_string_keys_round_trip() {
+    let ports: HashMap<u32, String> = HashMap::from([(80, "http".into()), (443, "https".into())]);
+    assert_eq!(from_str::<HashMap<u32, String>>(&to_string(&ports).unwrap()).unwrap(), ports);
+    let flags: HashMap<bool, i64> = HashMap::from([(true, 1), (false, -1)]);
+    assert_eq!(from_str::<HashMap<bool, i64>>(&to_string(&flags).unwrap()).unwrap(), flags);
+    let names: HashMap<char, f64> = HashMap::from([('x', 1.5)]);
+    assert_eq!(from_str::<HashMap<char, f64>>(&to_string(&names).unwrap()).unwrap(), names);
+}
+
+#[test]
+fn errors() {
+    assert!(from_str::<HashMap<u32, String>>(r#"} "port" "x" {"#).is_err());
+    assert!(from_str::<Service>(r#"} "name" "api" {"#).is_err());
+    assert!(from_str::<u32>(")1 \"a\" +(").is_err());
+}
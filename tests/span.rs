@@ -0,0 +1,49 @@
+use ason::span::LineIndex;
+
+#[test]
+fn lines_and_columns() {
+    let index = LineIndex::new("ab\ncd\n");
+    assert_eq!(index.line_count(), 3);
+    assert_eq!(index.line_col(0), (1, 1));
+    assert_eq!(index.line_col(2), (1, 3));
+    assert_eq!(index.line_col(3), (2, 1));
+    assert_eq!(index.line_col(6), (3, 1));
+}
+
+#[test]
+fn utf16_columns() {
+    // `é` is two bytes and one unit, `😀` four bytes and two units.
+    let text = "é😀x\n😀";
+    let index = LineIndex::new(text);
+    assert_eq!(index.utf16_position(2), (0, 1));
+    assert_eq!(index.utf16_position(6), (0, 3));
+    assert_eq!(index.utf16_position(12), (1, 2));
+    assert_eq!(index.line_col(6), (1, 3));
+    assert_eq!(index.utf16_offset(0, 3), 6);
+    assert_eq!(index.utf16_offset(1, 2), 12);
+    // An offset inside a character counts from its start, a column inside a
+    // surrogate pair moves past it.
+    assert_eq!(index.utf16_position(4), (0, 1));
+    assert_eq!(index.utf16_offset(0, 2), 6);
+}
+
+#[test]
+fn crlf_line_endings() {
+    let index = LineIndex::new("ab\r\ncd\r\n");
+    assert_eq!(index.line_count(), 3);
+    assert_eq!(index.line_col(4), (2, 1));
+    assert_eq!(index.utf16_position(5), (1, 1));
+    assert_eq!(index.utf16_offset(1, 1), 5);
+    assert_eq!(index.utf16_offset(0, 99), 2);
+    assert_eq!(index.utf16_offset(1, 99), 6);
+}
+
+#[test]
+fn offsets_past_the_end() {
+    let index = LineIndex::new("ab\nc");
+    assert_eq!(index.line_col(100), (2, 2));
+    assert_eq!(index.utf16_position(100), (1, 1));
+    assert_eq!(index.utf16_offset(1, 100), 4);
+    assert_eq!(index.utf16_offset(5, 0), 4);
+    assert_eq!(LineIndex::new("").line_col(3), (1, 1));
+}
@@ -0,0 +1,41 @@
+use ason::{AsonValue, Environment};
+
+fn evaluate(source: &str) -> Result<AsonValue, String> {
+    AsonValue::from_ason_string(source).map_err(|e| e.to_string())
+}
+
+/// Containers can't be arguments of an expression-s, so they are bound here.
+fn evaluate_with(source: &str, symbols: &[(&str, &str)]) -> Result<AsonValue, String> {
+    let mut env = Environment::new();
+    for (name, value) in symbols {
+        env.symbols.insert(name.to_string(), json(value));
+    }
+    AsonValue::from_ason_string_in(source, &mut env).map_err(|e| e.to_string())
+}
+
+fn json(s: &str) -> AsonValue {
+    AsonValue::from_json_string(s).unwrap()
+}
+
+#[test]
+fn object_spreads_apply_in_order() {
+    let base = r#"{"host": "db", "port": 5432}"#;
+    let value = evaluate_with(r#"} "port" 1 ...base "host" "prod-db" {"#, &[("base", base)]).unwrap();
+    assert_eq!(value, json(r#"{"host": "prod-db", "port": 5432}"#));
+    let value = evaluate(r#"} "a" 1 ...} "a" 2 "b" 2 { ...} { {"#).unwrap();
+    assert_eq!(value, json(r#"{"a": 2, "b": 2}"#));
+}
+
+#[test]
+fn array_spreads() {
+    let source = "] 1 ...middle 4 ...] [ ...] 5 [ [";
+    assert_eq!(evaluate_with(source, &[("middle", "[2, 3]")]).unwrap(), json("[1, 2, 3, 4, 5]"));
+}
+
+#[test]
+fn spreading_the_wrong_type() {
+    assert_eq!(evaluate("} ...] 1 [ {").unwrap_err(), "<string>:1:3: Only an object can be spread into an object, got array.");
+    assert_eq!(evaluate("] ...} {  [").unwrap_err(), "<string>:1:3: Only an array can be spread into an array, got object.");
+    assert_eq!(evaluate("] ...1 [").unwrap_err(), "<string>:1:3: Only an array can be spread into an array, got integer.");
+    assert!(evaluate("...] 1 [").is_err());
+}
@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use ason::io::MemoryIo;
+use ason::{AsonValue, Environment};
+
+#[test]
+fn trailing_tokens_are_rejected() {
+    let error = AsonValue::from_ason_string("} \"a\" 1 { garbage").unwrap_err();
+    assert_eq!(error.to_string(), "<string>:1:11: Unexpected token after the value: garbage");
+    assert!(AsonValue::from_ason_string("1 2").is_err());
+    assert!(AsonValue::from_ason_string("] 1 [ [").is_err());
+    assert_eq!(AsonValue::from_ason_string("").unwrap_err().msg, "Expected a value.");
+    assert_eq!(AsonValue::from_ason_string("  \\\\ only a comment\n").unwrap_err().msg, "Expected a value.");
+}
+
+#[test]
+fn documents_share_bindings() {
+    let source = "} \"a\" 1 {\n)2 \"x\" defvar(\n] x )x 1 +( [\n";
+    let mut env = Environment::new();
+    let documents: Vec<AsonValue> = AsonValue::documents_in(source, &mut env).collect::<Result<_, _>>().unwrap();
+    assert_eq!(documents, [
+        AsonValue::Object([("a".to_string(), 1.into())].into_iter().collect()),
+        2.into(),
+        AsonValue::from(vec![AsonValue::from(2), AsonValue::from(3)]),
+    ]);
+    assert_eq!(AsonValue::documents_in("", &mut env).count(), 0);
+}
+
+#[test]
+fn an_error_ends_the_stream() {
+    let mut env = Environment::new();
+    let mut documents = AsonValue::documents_in("1 )\"a\" 1 +( 3", &mut env);
+    assert_eq!(documents.next().unwrap().unwrap(), 1.into());
+    assert_eq!(documents.next().unwrap().unwrap_err().to_string(), "<string>:1:3: + expects numbers, got string.");
+    assert!(documents.next().is_none());
+
+    let mut documents = AsonValue::documents_in("1 \"unterminated", &mut env);
+    assert!(documents.next().unwrap().is_err());
+    assert!(documents.next().is_none());
+}
+
+#[test]
+fn file_documents_resolve_includes() {
+    let io = MemoryIo::new();
+    io.add_file("/app/one.ason", "1");
+    let mut env = Environment::new();
+    env.io = Box::new(io);
+    let documents: Vec<AsonValue> = AsonValue::file_documents_in(")\"one.ason\" include( 2", Path::new("/app/main.ason"), &mut env)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(documents, [1.into(), 2.into()]);
+}
@@ -0,0 +1,85 @@
+use ason::ast::AsonExpr;
+use ason::lexer::Lexer;
+use ason::limits::Limits;
+use ason::parser::Parser;
+use ason::vm::{Op, Program, Vm};
+use ason::{AsonValue, Environment};
+
+fn environment(max_steps: u64) -> Environment {
+    let mut env = Environment::with_limits(Limits { max_steps, ..Limits::default() });
+    env.add_constant("rate".into(), AsonValue::from(3));
+    env
+}
+
+fn expression(source: &str) -> AsonExpr {
+    let mut lexer = Lexer::new(source);
+    lexer.scan().unwrap();
+    let tokens = lexer.into_tokens();
+    Parser::new(&tokens, "test".into(), &mut environment(u64::MAX)).expression().unwrap()
+}
+
+/// Runs `source` on both the tree-walker and the VM with the same budget.
+fn both(source: &str, max_steps: u64) -> (String, String) {
+    let expr = expression(source);
+    let tree = expr.eval(&mut environment(max_steps));
+    let vm = Vm::new().run(&Program::compile(&expr), &mut environment(max_steps));
+    (format!("{:?}", tree), format!("{:?}", vm))
+}
+
+const SOURCES: &[&str] = &[
+    ")1 2 +(",
+    ")))1 rate *( 2 -( 4 /(",
+    ")\"a\" ))rate 1 +( \"b\" concat( concat(",
+    ")(",
+    ")) ( 1 +(",
+    ")))2 \"x\" defvar( x *( x +(",
+    ")undefined 1 +(",
+    ")1 2 rate(",
+    ")1 \"a\" +(",
+    ") )1 2 +( )0 \"+\" defvar( )1 2 +( concat(",
+];
+
+#[test]
+fn same_results_as_the_tree_walker() {
+    for source in SOURCES {
+        let (tree, vm) = both(source, u64::MAX);
+        assert_eq!(tree, vm, "{}", source);
+    }
+}
+
+#[test]
+fn same_step_counts_as_the_tree_walker() {
+    for source in SOURCES {
+        for max_steps in 0..20 {
+            let (tree, vm) = both(source, max_steps);
+            assert_eq!(tree, vm, "{} with {} steps", source, max_steps);
+        }
+    }
+}
+
+#[test]
+fn rebinding_a_called_symbol_is_seen() {
+    let (_, vm) = both(") )1 2 +( )0 \"+\" defvar( )1 2 +( concat(", u64::MAX);
+    assert!(vm.contains("NotAFunction"), "{}", vm);
+}
+
+#[test]
+fn symbols_share_slots() {
+    let program = Program::compile(&expression(")x x )x y +( +("));
+    assert_eq!(program.names(), ["x", "y", "+"]);
+    assert_eq!(
+        program.code(),
+        [Op::Enter, Op::Load(0), Op::Load(0), Op::Enter, Op::Load(0), Op::Load(1), Op::Call { callee: 2, argc: 2 }, Op::Call { callee: 2, argc: 3 }]
+    );
+}
+
+#[test]
+fn errors_leave_the_depth_as_it_was() {
+    let mut env = environment(u64::MAX);
+    let program = Program::compile(&expression(")))undefined 1 +( 2 +( 3 +("));
+    let mut vm = Vm::new();
+    for _ in 0..300 {
+        assert!(vm.run(&program, &mut env).is_err());
+    }
+    assert_eq!(vm.run(&Program::compile(&expression(")1 2 +(")), &mut env).unwrap(), AsonValue::from(3));
+}